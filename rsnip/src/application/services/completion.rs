@@ -1,12 +1,12 @@
 // application/services/completion.rs
 use crate::domain::snippet::Snippet;
-use crate::infrastructure::fuzzy;
+use crate::infrastructure::fuzzy::{self, PreviewOptions};
 use anyhow::Result;
 use fuzzy_matcher::skim::SkimMatcherV2;
 use std::cmp::Reverse;
 use tracing::instrument;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct CompletionService;
 
 impl CompletionService {
@@ -14,13 +14,19 @@ impl CompletionService {
         Self
     }
 
-    #[instrument(level = "debug")]
-    pub fn find_completion_interactive(&self, items: &[Snippet], user_input: &str) -> Result<Option<Snippet>> {
+    /// Select interactively with the finder, `preview` configures rendering and highlighting
+    #[instrument(level = "debug", skip(preview))]
+    pub fn find_completion_interactive(
+        &self,
+        items: &[Snippet],
+        user_input: &str,
+        preview: PreviewOptions,
+    ) -> Result<Option<Snippet>> {
         if items.is_empty() {
             return Ok(None);
         }
 
-        let selected_item = fuzzy::run_fuzzy_finder(items, user_input)?;
+        let selected_item = fuzzy::run_fuzzy_finder_with_preview(items, user_input, preview)?;
         Ok(selected_item.and_then(|name| items.iter().find(|item| item.name == name).cloned()))
     }
 
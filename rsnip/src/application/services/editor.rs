@@ -0,0 +1,139 @@
+// application/services/editor.rs
+use crate::application::services::SnippetManagementService;
+use crate::config::Settings;
+use crate::domain::content::SnippetContent;
+use crate::domain::editor::{language_matches, EditorSnippet, SnippetProvider};
+use crate::domain::output::{apply_output, Expansion, OutputTransform};
+use crate::domain::parser::{SnippetFormat, SnippetType};
+use crate::domain::template::interface::TemplateEngine;
+use anyhow::{anyhow, Result};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use tracing::{debug, instrument};
+
+/// Creates the template engine for snippets of a type
+pub type EngineFactory<'a> = Box<dyn Fn(&str) -> Result<Box<dyn TemplateEngine>> + 'a>;
+
+/// Offers snippets to editors, rendering templates with the engine of their type
+pub struct EditorService<'a> {
+    config: &'a Settings,
+    snippet_type: Option<String>,
+    engine_factory: EngineFactory<'a>,
+    engines: RefCell<HashMap<String, Box<dyn TemplateEngine>>>,
+}
+
+impl<'a> EditorService<'a> {
+    pub fn new(config: &'a Settings, engine_factory: EngineFactory<'a>) -> Self {
+        Self {
+            config,
+            snippet_type: None,
+            engine_factory,
+            engines: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Offer only snippets of `name`, or of its sources for a combined type
+    pub fn with_snippet_type(mut self, name: impl Into<String>) -> Self {
+        self.snippet_type = Some(name.into());
+        self
+    }
+
+    /// Concrete types to offer, every available one without a configured type
+    fn snippet_types(&self) -> Result<Vec<SnippetType>> {
+        let Some(name) = &self.snippet_type else {
+            return Ok(SnippetManagementService::new(self.config).available_types());
+        };
+
+        let names = self
+            .config
+            .get_combined_sources(name)
+            .unwrap_or_else(|| vec![name.clone()]);
+        names
+            .iter()
+            .map(|name| {
+                self.config
+                    .get_snippet_type(name)
+                    .ok_or_else(|| anyhow!("Unknown snippet type: {}", name))
+            })
+            .collect()
+    }
+
+    /// The snippet's output pipeline, or else the one of its type
+    fn transforms(&self, ctype: &str, output: Option<&[OutputTransform]>) -> Vec<OutputTransform> {
+        output
+            .map(<[OutputTransform]>::to_vec)
+            .unwrap_or_else(|| self.config.output_transforms(ctype))
+    }
+}
+
+impl SnippetProvider for EditorService<'_> {
+    #[instrument(level = "debug", skip(self))]
+    fn snippets(&self, language: Option<&str>) -> Result<Vec<EditorSnippet>> {
+        let management = SnippetManagementService::new(self.config);
+        let mut result = Vec::new();
+        for snippet_type in self.snippet_types()? {
+            let mut snippets = management.get_snippets(&snippet_type.name)?;
+            snippets.retain(|snippet| language_matches(snippet.language.as_deref(), language));
+            snippets.sort_by(|a, b| a.name.cmp(&b.name));
+            debug!("Offering {} snippets of type {}", snippets.len(), snippet_type.name);
+
+            for snippet in snippets {
+                // Without insert text the snippet is rendered on resolve, which reports failures
+                let insert_text = match &snippet.content {
+                    SnippetContent::Static(text) => insert_text(
+                        snippet_type.format,
+                        text,
+                        &self.transforms(&snippet_type.name, snippet.output.as_deref()),
+                    )
+                    .ok(),
+                    SnippetContent::Template { .. } => None,
+                };
+                result.push(EditorSnippet {
+                    ctype: snippet_type.name.clone(),
+                    description: snippet.description(),
+                    language: snippet.language,
+                    source: snippet.content.get_content().to_string(),
+                    name: snippet.name,
+                    insert_text,
+                });
+            }
+        }
+        Ok(result)
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    fn resolve(&self, ctype: &str, name: &str) -> Result<String> {
+        let snippet_type = self
+            .config
+            .get_snippet_type(ctype)
+            .ok_or_else(|| anyhow!("Unknown snippet type: {}", ctype))?;
+        let snippet = SnippetManagementService::new(self.config)
+            .get_snippets(ctype)?
+            .into_iter()
+            .find(|snippet| snippet.name == name)
+            .ok_or_else(|| anyhow!("No matching snippet found for '{}/{}'", ctype, name))?;
+
+        let mut engines = self.engines.borrow_mut();
+        if !engines.contains_key(ctype) {
+            engines.insert(ctype.to_string(), (self.engine_factory)(ctype)?);
+        }
+        let rendered = engines[ctype].render(ctype, &snippet.content)?;
+        insert_text(
+            snippet_type.format,
+            &rendered,
+            &self.transforms(ctype, snippet.output.as_deref()),
+        )
+    }
+}
+
+/// Shape rendered `text` with `transforms` into LSP snippet syntax
+fn insert_text(format: SnippetFormat, text: &str, transforms: &[OutputTransform]) -> Result<String> {
+    match format {
+        // Native snippets only mark the cursor, everything else is literal text
+        SnippetFormat::Default => Expansion::render(text, transforms)
+            .map(|expansion| expansion.to_snippet_syntax())
+            .map_err(|e| anyhow!(e)),
+        // VS Code and scls snippets are written in snippet syntax already
+        SnippetFormat::Scls | SnippetFormat::VCode => Ok(apply_output(text, transforms)),
+    }
+}
@@ -1,9 +1,15 @@
 // application/services/management.rs
 use crate::config::Settings;
-use crate::domain::parser::SnippetType;
+use crate::domain::parser::{SnippetFormat, SnippetType};
 use crate::domain::snippet::Snippet;
+use crate::domain::template::errors::TemplateError;
+use crate::domain::template::interface::SnippetResolver;
 use crate::infrastructure::parsers::SnippetParserFactory;
 use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+use std::time::SystemTime;
 use tracing::{debug, instrument};
 
 pub struct SnippetManagementService<'a> {
@@ -18,15 +24,26 @@ impl<'a> SnippetManagementService<'a> {
     /// Get snippets for a given snippet type, handling both concrete and combined types
     #[instrument(level = "debug", skip(self))]
     pub fn get_snippets(&self, snippet_type: &str) -> Result<Vec<Snippet>> {
+        Ok(self
+            .get_typed_snippets(snippet_type)?
+            .into_iter()
+            .map(|(_, snippet)| snippet)
+            .collect())
+    }
+
+    /// Snippets of a snippet type, each with the concrete type it was loaded from;
+    /// for a combined type that is the source holding the snippet
+    #[instrument(level = "debug", skip(self))]
+    pub fn get_typed_snippets(&self, snippet_type: &str) -> Result<Vec<(String, Snippet)>> {
         if let Some(sources) = self.config.get_combined_sources(snippet_type) {
             debug!("Loading combined snippets from sources: {:?}", sources);
             let mut all_snippets = Vec::new();
 
             for source in sources {
                 if let Some(concrete_type) = self.config.get_snippet_type(&source) {
-                    let mut source_snippets = self.get_concrete_snippets(&concrete_type)
+                    let source_snippets = self.get_concrete_snippets(&concrete_type)
                         .with_context(|| format!("Failed to load snippets from source '{}'", source))?;
-                    all_snippets.append(&mut source_snippets);
+                    all_snippets.extend(source_snippets.into_iter().map(|snippet| (source.clone(), snippet)));
                 }
             }
 
@@ -34,14 +51,100 @@ impl<'a> SnippetManagementService<'a> {
         } else {
             let concrete_type = self.config.get_snippet_type(snippet_type)
                 .ok_or_else(|| anyhow::anyhow!("Unknown snippet type: {}", snippet_type))?;
-            self.get_concrete_snippets(&concrete_type)
+            Ok(self
+                .get_concrete_snippets(&concrete_type)?
+                .into_iter()
+                .map(|snippet| (concrete_type.name.clone(), snippet))
+                .collect())
         }
     }
 
+    /// Concrete types to list when no type is given, sorted by name.
+    /// Types whose source file does not exist are skipped.
+    pub fn available_types(&self) -> Vec<SnippetType> {
+        let mut types: Vec<SnippetType> = self
+            .config
+            .snippet_types
+            .keys()
+            .filter_map(|name| self.config.get_snippet_type(name))
+            .filter(|concrete| concrete.source_file.exists())
+            .collect();
+        types.sort_by(|a, b| a.name.cmp(&b.name));
+        types
+    }
+
     fn get_concrete_snippets(&self, snippet_type: &SnippetType) -> Result<Vec<Snippet>> {
         debug!("Loading snippets from {}", snippet_type.source_file.display());
         let parser = SnippetParserFactory::create(snippet_type.format);
-        parser.parse(&snippet_type.source_file)
-            .with_context(|| format!("Failed to parse snippets from {}", snippet_type.source_file.display()))
+        let mut snippets = parser.parse(&snippet_type.source_file)
+            .with_context(|| format!("Failed to parse snippets from {}", snippet_type.source_file.display()))?;
+
+        // Native snippet files named after their language, e.g. `docker.sh`, set a default language
+        if snippet_type.format == SnippetFormat::Default {
+            let extension = snippet_type.source_file.extension().and_then(|ext| ext.to_str());
+            if let Some(extension) = extension.filter(|ext| *ext != "txt") {
+                for snippet in snippets.iter_mut().filter(|s| s.language.is_none()) {
+                    snippet.language = Some(extension.to_string());
+                }
+            }
+        }
+        Ok(snippets)
+    }
+}
+
+/// Resolves template references like `shell:header` through `SnippetManagementService`.
+/// Names without a type are looked up in `default_type`; loaded types are cached until
+/// one of their files is modified.
+pub struct SnippetLibrary {
+    config: Settings,
+    default_type: String,
+    cache: Mutex<HashMap<String, LoadedType>>,
+}
+
+/// Snippets of a type, with the modification times of its files when they were loaded
+type LoadedType = (Vec<Option<SystemTime>>, Vec<Snippet>);
+
+impl SnippetLibrary {
+    pub fn new(config: Settings, default_type: &str) -> Self {
+        Self {
+            config,
+            default_type: default_type.to_string(),
+            cache: Mutex::new(HashMap::new()),
+        }
     }
-}
\ No newline at end of file
+
+    /// Modification times of the files `snippet_type` is loaded from
+    fn modified(&self, snippet_type: &str) -> Vec<Option<SystemTime>> {
+        self.config
+            .get_combined_sources(snippet_type)
+            .unwrap_or_else(|| vec![snippet_type.to_string()])
+            .iter()
+            .filter_map(|name| self.config.get_snippet_type(name))
+            .map(|concrete| fs::metadata(&concrete.source_file).and_then(|m| m.modified()).ok())
+            .collect()
+    }
+}
+
+impl SnippetResolver for SnippetLibrary {
+    #[instrument(level = "debug", skip(self))]
+    fn resolve(&self, reference: &str) -> Result<Option<String>, TemplateError> {
+        let (snippet_type, name) = reference
+            .split_once(':')
+            .unwrap_or((self.default_type.as_str(), reference));
+
+        let modified = self.modified(snippet_type);
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        if cache.get(snippet_type).is_none_or(|(loaded, _)| *loaded != modified) {
+            let snippets = SnippetManagementService::new(&self.config)
+                .get_snippets(snippet_type)
+                .map_err(|e| TemplateError::Reference(format!("{}: {:#}", reference, e)))?;
+            cache.insert(snippet_type.to_string(), (modified, snippets));
+        }
+
+        Ok(cache[snippet_type]
+            .1
+            .iter()
+            .find(|snippet| snippet.name == name)
+            .map(|snippet| snippet.content.get_content().to_string()))
+    }
+}
@@ -0,0 +1,44 @@
+// application/services/menu.rs
+use crate::application::services::SnippetManagementService;
+use crate::config::Settings;
+use crate::domain::menu::MenuEntry;
+use anyhow::Result;
+use tracing::{debug, instrument};
+
+pub struct MenuService<'a> {
+    config: &'a Settings,
+}
+
+impl<'a> MenuService<'a> {
+    pub fn new(config: &'a Settings) -> Self {
+        Self { config }
+    }
+
+    /// Entries of `snippet_type`, or of every available concrete type if `None`
+    #[instrument(level = "debug", skip(self))]
+    pub fn entries(&self, snippet_type: Option<&str>) -> Result<Vec<MenuEntry>> {
+        let management = SnippetManagementService::new(self.config);
+        let types: Vec<String> = match snippet_type {
+            Some(name) => vec![name.to_string()],
+            None => management
+                .available_types()
+                .into_iter()
+                .map(|concrete| concrete.name)
+                .collect(),
+        };
+
+        let mut entries = Vec::new();
+        for ctype in types {
+            let mut snippets = management.get_snippets(&ctype)?;
+            snippets.sort_by(|a, b| a.name.cmp(&b.name));
+            debug!("Listing {} snippets of type {}", snippets.len(), ctype);
+
+            entries.extend(snippets.into_iter().map(|snippet| MenuEntry {
+                ctype: ctype.clone(),
+                description: snippet.description(),
+                name: snippet.name,
+            }));
+        }
+        Ok(entries)
+    }
+}
@@ -1,7 +1,15 @@
 pub mod management;
 pub mod completion;
 pub mod template;
+pub mod validation;
+pub mod scaffold;
+pub mod menu;
+pub mod editor;
 
-pub use management::SnippetManagementService;
+pub use management::{SnippetLibrary, SnippetManagementService};
 pub use completion::CompletionService;
 pub use template::TemplateProcessingService;
+pub use validation::{SnippetDiagnostic, ValidationService};
+pub use scaffold::ScaffoldPlan;
+pub use menu::MenuService;
+pub use editor::{EditorService, EngineFactory};
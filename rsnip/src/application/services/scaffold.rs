@@ -0,0 +1,120 @@
+// application/services/scaffold.rs
+use crate::domain::manifest::ManifestEntry;
+use anyhow::{anyhow, Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, instrument};
+
+/// Files and directories a file-template snippet creates below `dest`
+#[derive(Clone, Debug)]
+pub struct ScaffoldPlan {
+    pub dest: PathBuf,
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl ScaffoldPlan {
+    pub fn new(dest: impl Into<PathBuf>, entries: Vec<ManifestEntry>) -> Self {
+        Self {
+            dest: dest.into(),
+            entries,
+        }
+    }
+
+    /// Relative paths of files that already exist
+    pub fn conflicts(&self) -> Vec<&Path> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.content.is_some() && self.dest.join(&entry.path).exists())
+            .map(|entry| entry.path.as_path())
+            .collect()
+    }
+
+    /// Directory tree of the plan, existing files are marked
+    pub fn tree(&self) -> String {
+        let mut root = TreeNode::default();
+        for entry in &self.entries {
+            let mut node = &mut root;
+            for component in entry.path.iter() {
+                node = node
+                    .children
+                    .entry(component.to_string_lossy().to_string())
+                    .or_default();
+            }
+            node.is_file = entry.content.is_some();
+            node.exists = node.is_file && self.dest.join(&entry.path).exists();
+        }
+
+        let mut tree = format!("{}\n", self.dest.display());
+        root.render("", &mut tree);
+        tree
+    }
+
+    /// Create all directories and files; existing files are only replaced with `force`
+    #[instrument(level = "debug", skip(self))]
+    pub fn write(&self, force: bool) -> Result<()> {
+        let conflicts = self.conflicts();
+        if !conflicts.is_empty() && !force {
+            return Err(anyhow!(
+                "{} file(s) already exist, use --force to overwrite: {}",
+                conflicts.len(),
+                conflicts
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
+        for entry in &self.entries {
+            let path = self.dest.join(&entry.path);
+            match &entry.content {
+                None => fs::create_dir_all(&path)
+                    .with_context(|| format!("Failed to create directory {}", path.display()))?,
+                Some(content) => {
+                    if let Some(parent) = path.parent() {
+                        fs::create_dir_all(parent).with_context(|| {
+                            format!("Failed to create directory {}", parent.display())
+                        })?;
+                    }
+                    debug!("Writing {:?}", path);
+                    fs::write(&path, content)
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct TreeNode {
+    children: BTreeMap<String, TreeNode>,
+    is_file: bool,
+    exists: bool,
+}
+
+impl TreeNode {
+    fn render(&self, prefix: &str, out: &mut String) {
+        let count = self.children.len();
+        for (idx, (name, child)) in self.children.iter().enumerate() {
+            let last = idx + 1 == count;
+            let suffix = match (child.is_file, child.exists) {
+                (false, _) => "/",
+                (true, true) => "  (exists)",
+                (true, false) => "",
+            };
+            out.push_str(&format!(
+                "{}{}{}{}\n",
+                prefix,
+                if last { "└── " } else { "├── " },
+                name,
+                suffix
+            ));
+            child.render(
+                &format!("{}{}", prefix, if last { "    " } else { "│   " }),
+                out,
+            );
+        }
+    }
+}
@@ -1,23 +1,52 @@
 // application/services/template.rs
+use crate::domain::output::{apply_output, Expansion, OutputTransform};
 use crate::domain::snippet::Snippet;
 use crate::domain::template::interface::TemplateEngine;
 use crate::infrastructure::clipboard::copy_to_clipboard;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use std::sync::Arc;
 use tracing::instrument;
 
 pub struct TemplateProcessingService {
-    template_engine: Box<dyn TemplateEngine>,
+    template_engine: Arc<dyn TemplateEngine>,
 }
 
 impl TemplateProcessingService {
     pub fn new(template_engine: Box<dyn TemplateEngine>) -> Self {
-        Self { template_engine }
+        Self {
+            template_engine: Arc::from(template_engine),
+        }
+    }
+
+    pub fn engine(&self) -> &dyn TemplateEngine {
+        self.template_engine.as_ref()
+    }
+
+    /// Engine handle for rendering outside this service, e.g. previews
+    pub fn shared_engine(&self) -> Arc<dyn TemplateEngine> {
+        self.template_engine.clone()
+    }
+
+    /// Render `snippet` of `snippet_type` and shape it with its own output pipeline, or else
+    /// `type_output`. Cursor markers are left in the text.
+    #[instrument(level = "debug", skip(self))]
+    pub fn render_output(&self, snippet_type: &str, snippet: &Snippet, type_output: &[OutputTransform]) -> Result<String> {
+        let rendered = self.template_engine.render(snippet_type, &snippet.content)?;
+        Ok(apply_output(&rendered, snippet.output.as_deref().unwrap_or(type_output)))
+    }
+
+    /// Render like `render_output` for inline expansion: the cursor marker is stripped
+    /// and its position kept
+    #[instrument(level = "debug", skip(self))]
+    pub fn render_expansion(&self, snippet_type: &str, snippet: &Snippet, type_output: &[OutputTransform]) -> Result<Expansion> {
+        let rendered = self.template_engine.render(snippet_type, &snippet.content)?;
+        Expansion::render(&rendered, snippet.output.as_deref().unwrap_or(type_output)).map_err(|e| anyhow!(e))
     }
 
     #[instrument(level = "debug", skip(self))]
-    pub fn process_and_copy(&self, snippet: &Snippet) -> Result<String> {
-        let rendered = self.template_engine.render(&snippet.content)?;
-        copy_to_clipboard(&rendered)?;
-        Ok(rendered)
+    pub fn process_and_copy(&self, snippet_type: &str, snippet: &Snippet, type_output: &[OutputTransform]) -> Result<String> {
+        let output = self.render_output(snippet_type, snippet, type_output)?;
+        copy_to_clipboard(&output)?;
+        Ok(output)
     }
 }
\ No newline at end of file
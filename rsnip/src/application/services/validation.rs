@@ -0,0 +1,203 @@
+// application/services/validation.rs
+use crate::application::services::SnippetManagementService;
+use crate::config::Settings;
+use crate::domain::errors::SnippetError;
+use crate::domain::output::parse_output;
+use crate::domain::parser::{SnippetFormat, SnippetType};
+use crate::domain::snippet::Snippet;
+use crate::domain::template::interface::TemplateEngine;
+use anyhow::{anyhow, Result};
+use std::fmt;
+use std::path::PathBuf;
+use tracing::{debug, instrument};
+
+/// Problem in a snippet file, printed as `file:line: snippet: message`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnippetDiagnostic {
+    pub file: PathBuf,
+    pub line: usize,
+    pub snippet: String,
+    pub message: String,
+}
+
+impl fmt::Display for SnippetDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.snippet.is_empty() {
+            write!(f, "{}:{}: {}", self.file.display(), self.line, self.message)
+        } else {
+            write!(
+                f,
+                "{}:{}: {}: {}",
+                self.file.display(),
+                self.line,
+                self.snippet,
+                self.message
+            )
+        }
+    }
+}
+
+pub struct ValidationService<'a> {
+    config: &'a Settings,
+}
+
+impl<'a> ValidationService<'a> {
+    pub fn new(config: &'a Settings) -> Self {
+        Self { config }
+    }
+
+    /// Check all templates of `snippet_type`, or of every configured type if `None`.
+    /// Source files that do not exist are skipped when checking every type.
+    #[instrument(level = "debug", skip(self, engine))]
+    pub fn check(
+        &self,
+        engine: &dyn TemplateEngine,
+        snippet_type: Option<&str>,
+    ) -> Result<Vec<SnippetDiagnostic>> {
+        let mut diagnostics = Vec::new();
+
+        for concrete_type in self.concrete_types(snippet_type)? {
+            if snippet_type.is_none() && !concrete_type.source_file.exists() {
+                debug!("Skipping missing source file {:?}", concrete_type.source_file);
+                continue;
+            }
+
+            let snippets = match SnippetManagementService::new(self.config)
+                .get_snippets(&concrete_type.name)
+            {
+                Ok(snippets) => snippets,
+                Err(e) => {
+                    let line = match e.downcast_ref::<SnippetError>() {
+                        Some(SnippetError::InvalidFormat { line, .. }) => *line,
+                        _ => 1,
+                    };
+                    diagnostics.push(SnippetDiagnostic {
+                        file: concrete_type.source_file.clone(),
+                        line,
+                        snippet: String::new(),
+                        message: format!("{:#}", e),
+                    });
+                    continue;
+                }
+            };
+
+            let file_content =
+                std::fs::read_to_string(&concrete_type.source_file).unwrap_or_default();
+            if concrete_type.format == SnippetFormat::Default {
+                diagnostics.extend(output_header_issues(&concrete_type, &file_content));
+            }
+            for snippet in &snippets {
+                for issue in engine.check(&concrete_type.name, &snippet.content) {
+                    diagnostics.push(SnippetDiagnostic {
+                        file: concrete_type.source_file.clone(),
+                        line: locate(&concrete_type, &file_content, snippet, issue.line),
+                        snippet: snippet.name.clone(),
+                        message: issue.message,
+                    });
+                }
+            }
+        }
+
+        Ok(diagnostics)
+    }
+
+    /// Concrete types to check, expanding combined types into their sources
+    fn concrete_types(&self, snippet_type: Option<&str>) -> Result<Vec<SnippetType>> {
+        let mut names: Vec<String> = match snippet_type {
+            Some(name) => match self.config.get_combined_sources(name) {
+                Some(sources) => sources,
+                None if self.config.get_snippet_type(name).is_some() => vec![name.to_string()],
+                None => return Err(anyhow!("Unknown snippet type: {}", name)),
+            },
+            None => self.config.snippet_types.keys().cloned().collect(),
+        };
+        names.sort();
+        names.dedup();
+
+        Ok(names
+            .iter()
+            .filter_map(|name| self.config.get_snippet_type(name))
+            .collect())
+    }
+}
+
+/// `: output:` header lines naming unknown transforms; the parser keeps them as comments
+fn output_header_issues(snippet_type: &SnippetType, file_content: &str) -> Vec<SnippetDiagnostic> {
+    let mut issues = Vec::new();
+    // Snippet whose header is being read, until its first content line
+    let mut header: Option<&str> = None;
+
+    for (idx, line) in file_content.lines().enumerate() {
+        let trimmed = line.trim();
+        if let Some(name) = trimmed.strip_prefix("--- ") {
+            header = Some(name);
+            continue;
+        }
+        let Some(name) = header else {
+            continue;
+        };
+        match trimmed.strip_prefix(':') {
+            Some(comment) => {
+                if let Some(Err(reason)) = comment.trim().strip_prefix("output:").map(parse_output) {
+                    issues.push(SnippetDiagnostic {
+                        file: snippet_type.source_file.clone(),
+                        line: idx + 1,
+                        snippet: name.to_string(),
+                        message: reason,
+                    });
+                }
+            }
+            None if trimmed.is_empty() => {}
+            None => header = None,
+        }
+    }
+    issues
+}
+
+/// File line of `template_line` in `snippet`. Only the default format maps lines
+/// inside the snippet, other formats point at the line naming the snippet.
+fn locate(
+    snippet_type: &SnippetType,
+    file_content: &str,
+    snippet: &Snippet,
+    template_line: Option<usize>,
+) -> usize {
+    let lines: Vec<&str> = file_content.lines().collect();
+
+    if snippet_type.format != SnippetFormat::Default {
+        let quoted = format!("\"{}\"", snippet.name);
+        return lines
+            .iter()
+            .position(|line| line.contains(&quoted))
+            .map_or(1, |idx| idx + 1);
+    }
+
+    let header = format!("--- {}", snippet.name);
+    let Some(start) = lines.iter().position(|line| line.trim() == header) else {
+        return 1;
+    };
+    let Some(target) = template_line else {
+        return start + 1;
+    };
+
+    // Mirror the default parser: comments and leading or repeated blank lines are not content
+    let mut content_lines = 0;
+    let mut last_line_empty = false;
+    for (idx, line) in lines.iter().enumerate().skip(start + 1) {
+        let trimmed = line.trim();
+        if trimmed == "---" || trimmed.starts_with("--- ") {
+            break;
+        }
+        if trimmed.starts_with(':') {
+            continue;
+        }
+        if !trimmed.is_empty() || (content_lines > 0 && !last_line_empty) {
+            content_lines += 1;
+            if content_lines == target {
+                return idx + 1;
+            }
+        }
+        last_line_empty = trimmed.is_empty();
+    }
+    start + 1
+}
@@ -1,23 +1,38 @@
 // Updated snippet_service.rs as facade
-use crate::application::services::{SnippetManagementService, CompletionService, TemplateProcessingService};
+use crate::application::services::{SnippetDiagnostic, SnippetManagementService, CompletionService, MenuService, ScaffoldPlan, TemplateProcessingService, ValidationService};
 use crate::config::Settings;
 use crate::domain::snippet::Snippet;
-use anyhow::Result;
-use tracing::instrument;
+use crate::domain::manifest::parse_manifest;
+use crate::domain::menu::MenuEntry;
+use crate::domain::output::Expansion;
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{instrument, warn};
 use crate::domain::template::interface::TemplateEngine;
+use crate::infrastructure::fuzzy::PreviewOptions;
+use crate::infrastructure::highlight::Highlighter;
 
 pub struct SnippetService<'a> {
+    config: &'a Settings,
     management: SnippetManagementService<'a>,
     completion: CompletionService,
     template: TemplateProcessingService,
+    validation: ValidationService<'a>,
+    menu: MenuService<'a>,
+    highlighter: Option<Arc<Highlighter>>,
 }
 
 impl<'a> SnippetService<'a> {
     pub fn new(template_engine: Box<dyn TemplateEngine>, config: &'a Settings) -> Self {
         Self {
+            config,
             management: SnippetManagementService::new(config),
             completion: CompletionService::new(),
             template: TemplateProcessingService::new(template_engine),
+            validation: ValidationService::new(config),
+            menu: MenuService::new(config),
+            highlighter: create_highlighter(config.preview_theme.as_deref()),
         }
     }
 
@@ -25,10 +40,27 @@ impl<'a> SnippetService<'a> {
         self.management.get_snippets(snippet_type)
     }
 
+    /// Lint templates of one type, or of all types if `snippet_type` is `None`
+    #[instrument(level = "debug", skip(self))]
+    pub fn check_snippets(&self, snippet_type: Option<&str>) -> Result<Vec<SnippetDiagnostic>> {
+        self.validation.check(self.template.engine(), snippet_type)
+    }
+
+    /// Launcher menu entries of one type, or of all types if `snippet_type` is `None`
+    pub fn menu_entries(&self, snippet_type: Option<&str>) -> Result<Vec<MenuEntry>> {
+        self.menu.entries(snippet_type)
+    }
+
     #[instrument(level = "debug", skip(self))]
     pub fn find_completion_interactive(&self, completion_type: &str, user_input: &str) -> Result<Option<Snippet>> {
-        let items = self.get_snippets(completion_type)?;
-        self.completion.find_completion_interactive(&items, user_input)
+        let (snippet_types, items): (Vec<String>, Vec<Snippet>) =
+            self.management.get_typed_snippets(completion_type)?.into_iter().unzip();
+        let preview = PreviewOptions {
+            engine: Some(self.template.shared_engine()),
+            highlighter: self.highlighter.clone(),
+            snippet_types,
+        };
+        self.completion.find_completion_interactive(&items, user_input, preview)
     }
 
     #[instrument(level = "debug", skip(self))]
@@ -43,19 +75,91 @@ impl<'a> SnippetService<'a> {
         Ok(self.completion.find_completion_exact(&items, user_input))
     }
 
+    /// The snippet picked by `find` among the snippets of `completion_type`, with the concrete
+    /// type it comes from; snippets render with the vars and output of that type
+    fn find_with_source_type(
+        &self,
+        completion_type: &str,
+        find: impl FnOnce(&[Snippet]) -> Option<Snippet>,
+    ) -> Result<Option<(String, Snippet)>> {
+        let (snippet_types, items): (Vec<String>, Vec<Snippet>) =
+            self.management.get_typed_snippets(completion_type)?.into_iter().unzip();
+        Ok(find(&items).and_then(|found| {
+            let idx = items.iter().position(|item| *item == found)?;
+            Some((snippet_types[idx].clone(), found))
+        }))
+    }
+
+    /// The snippet named exactly `input`, with the concrete type it comes from
+    fn find_exact_with_source_type(&self, completion_type: &str, input: &str) -> Result<Option<(String, Snippet)>> {
+        self.find_with_source_type(completion_type, |items| self.completion.find_completion_exact(items, input))
+    }
+
     #[instrument(level = "debug", skip(self))]
     pub fn copy_snippet_to_clipboard(&self, completion_type: &str, input: &str, exact: bool) -> Result<Option<(Snippet, String)>> {
         let item = if exact {
-            self.find_completion_exact(completion_type, input)?
+            self.find_exact_with_source_type(completion_type, input)?
         } else {
-            self.find_completion_fuzzy(completion_type, input)?
+            self.find_with_source_type(completion_type, |items| self.completion.find_completion_fuzzy(items, input))?
         };
 
-        if let Some(completion_item) = item.clone() {
-            let rendered = self.template.process_and_copy(&completion_item)?;
+        if let Some((snippet_type, completion_item)) = item {
+            let type_output = self.config.output_transforms(&snippet_type);
+            let rendered = self.template.process_and_copy(&snippet_type, &completion_item, &type_output)?;
             Ok(Some((completion_item, rendered)))
         } else {
             Ok(None)
         }
     }
-}
\ No newline at end of file
+
+    /// Render the snippet named exactly `input` without copying it, to deliver it elsewhere
+    #[instrument(level = "debug", skip(self))]
+    pub fn render_snippet(&self, completion_type: &str, input: &str) -> Result<Option<(Snippet, String)>> {
+        match self.find_exact_with_source_type(completion_type, input)? {
+            Some((snippet_type, snippet)) => {
+                let type_output = self.config.output_transforms(&snippet_type);
+                let rendered = self.template.render_output(&snippet_type, &snippet, &type_output)?;
+                Ok(Some((snippet, rendered)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Render the snippet named exactly `input` for inline expansion, without copying it
+    #[instrument(level = "debug", skip(self))]
+    pub fn expand_snippet(&self, completion_type: &str, input: &str) -> Result<Option<Expansion>> {
+        match self.find_exact_with_source_type(completion_type, input)? {
+            Some((snippet_type, snippet)) => {
+                let type_output = self.config.output_transforms(&snippet_type);
+                Ok(Some(self.template.render_expansion(&snippet_type, &snippet, &type_output)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Render the file-template snippet `name` into a plan for files below `dest`
+    #[instrument(level = "debug", skip(self))]
+    pub fn plan_scaffold(&self, completion_type: &str, name: &str, dest: &Path) -> Result<ScaffoldPlan> {
+        let (snippet_type, snippet) = self
+            .find_exact_with_source_type(completion_type, name)?
+            .ok_or_else(|| anyhow!("No matching snippet found for '{}'", name))?;
+        let rendered = self.template.engine().render(&snippet_type, &snippet.content)?;
+        let entries = parse_manifest(&rendered)
+            .map_err(|e| anyhow!("Snippet '{}' is not a file template: {}", name, e))?;
+        Ok(ScaffoldPlan::new(dest, entries))
+    }
+}
+
+/// Preview highlighter for the configured theme; "none" disables highlighting
+fn create_highlighter(theme: Option<&str>) -> Option<Arc<Highlighter>> {
+    if theme == Some("none") {
+        return None;
+    }
+    match Highlighter::new(theme) {
+        Ok(highlighter) => Some(Arc::new(highlighter)),
+        Err(e) => {
+            warn!("{}, using default theme", e);
+            Highlighter::new(None).ok().map(Arc::new)
+        }
+    }
+}
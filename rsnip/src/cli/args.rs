@@ -1,5 +1,7 @@
-use clap::{Parser, Subcommand};
-use clap_complete::Shell;
+use chrono::{DateTime, FixedOffset};
+use clap::{Parser, Subcommand, ValueEnum};
+use crate::infrastructure::complete::ShellKind;
+use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
 #[command(author, version, about, long_about = None)] // Read from `Cargo.toml`
@@ -11,7 +13,11 @@ pub struct Cli {
 
     /// Generate shell completion scripts
     #[arg(long = "generate", value_enum)]
-    pub generator: Option<Shell>,
+    pub generator: Option<ShellKind>,
+
+    /// Print tmux key bindings opening the snippet finder in a popup
+    #[arg(long = "generate-tmux", conflicts_with = "generator")]
+    pub generate_tmux: bool,
 
     /// Print default configuration to stdout
     #[arg(long = "generate-config")]
@@ -21,10 +27,59 @@ pub struct Cli {
     #[arg(long = "info")]
     pub info: bool,
 
+    /// Render templates as if it were this RFC3339 time, e.g. 2025-01-15T09:00:00+01:00
+    #[arg(long, global = true, env = "RSNIP_NOW", value_parser = DateTime::parse_from_rfc3339)]
+    pub now: Option<DateTime<FixedOffset>>,
+
+    /// Seed for random template functions (uuid4, random_int, random_string)
+    #[arg(long, global = true, env = "RSNIP_SEED")]
+    pub seed: Option<u64>,
+
+    /// Template variable as KEY=VALUE, overriding config vars. Can be repeated
+    #[arg(long = "var", global = true, value_name = "KEY=VALUE", value_parser = parse_var)]
+    pub vars: Vec<(String, String)>,
+
+    /// Answer for a prompt(), confirm() or choose() question as LABEL=VALUE. Can be repeated
+    #[arg(long = "answer", global = true, value_name = "LABEL=VALUE", value_parser = parse_var)]
+    pub answers: Vec<(String, String)>,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
 
+fn parse_var(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected KEY=VALUE, got '{}'", s)),
+    }
+}
+
+/// Output of `expand`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ExpandFormat {
+    /// Cursor offset on the first line, then the text
+    #[default]
+    Lines,
+    /// JSON object with text, cursor offset, line and column
+    Json,
+    /// Only the text, e.g. to print or run the snippet from a shell alias
+    Text,
+}
+
+/// Where `copy` delivers the rendered snippet
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputTarget {
+    /// System clipboard
+    #[default]
+    Clipboard,
+    /// Paste into a tmux pane, without pressing enter
+    Tmux,
+    /// Load into a tmux paste buffer, to paste with prefix + ]
+    TmuxBuffer,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Commands {
     /// List available snippet types
@@ -41,6 +96,9 @@ pub enum Commands {
         /// Filter snippets by prefix
         #[arg(long)]
         prefix: Option<String>,
+        /// Print only snippet names, one per line, e.g. for shell completion
+        #[arg(long)]
+        names_only: bool,
     },
     /// Edit snippet in system editor
     Edit {
@@ -71,6 +129,64 @@ pub enum Commands {
         /// The text to copy
         #[arg(long)]
         input: String,
+        /// Where to deliver the rendered snippet
+        #[arg(long, value_enum, default_value_t)]
+        to: OutputTarget,
+        /// Tmux pane for `--to tmux`, e.g. %3; defaults to the active pane
+        #[arg(long, requires = "to")]
+        pane: Option<String>,
+    },
+    /// Render a snippet for inline expansion: prints the cursor offset, then the text
+    Expand {
+        /// Type of completion
+        #[arg(long)]
+        ctype: Option<String>,
+        /// The snippet to expand
+        #[arg(long)]
+        input: String,
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: ExpandFormat,
+    },
+    /// List snippets for rofi, dmenu or fuzzel and copy the selected one
+    Menu {
+        /// Type of snippets to list, all types if omitted
+        #[arg(long)]
+        ctype: Option<String>,
+        /// Run this dmenu-compatible command, e.g. "fuzzel --dmenu", instead of speaking rofi's script protocol
+        #[arg(long, value_name = "COMMAND")]
+        dmenu: Option<String>,
+        /// Entry selected in rofi, passed by rofi
+        selection: Option<String>,
+    },
+    /// Check snippet templates for errors without rendering them
+    Check {
+        /// Type of snippets to check, all types if omitted
+        #[arg(long)]
+        ctype: Option<String>,
+    },
+    /// Create files and directories from a file-template snippet
+    Scaffold {
+        /// Type of completion
+        #[arg(long)]
+        ctype: Option<String>,
+        /// The file-template snippet
+        name: String,
+        /// Directory to create the files in
+        #[arg(long, default_value = ".")]
+        dest: PathBuf,
+        /// Overwrite existing files
+        #[arg(long)]
+        force: bool,
+        /// Only show the files that would be created
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Serve snippets as completions to editors over the Language Server Protocol on stdio
+    Lsp {
+        /// Type of snippets to offer, all types if omitted
+        #[arg(long)]
+        ctype: Option<String>,
     },
 }
 
@@ -84,4 +200,101 @@ mod tests {
         let args = Cli::parse_from(["rsnip", "--generate-config"]);
         assert!(args.generate_config);
     }
+
+    #[test]
+    fn given_now_and_seed_after_subcommand_when_parsing_then_sets_global_options() {
+        let args = Cli::parse_from([
+            "rsnip",
+            "copy",
+            "--input",
+            "x",
+            "--now",
+            "2025-01-15T09:00:00+01:00",
+            "--seed",
+            "42",
+        ]);
+        assert_eq!(
+            args.now.unwrap().to_rfc3339(),
+            "2025-01-15T09:00:00+01:00"
+        );
+        assert_eq!(args.seed, Some(42));
+    }
+
+    #[test]
+    fn given_repeated_var_when_parsing_then_collects_key_value_pairs() {
+        let args = Cli::parse_from([
+            "rsnip",
+            "copy",
+            "--input",
+            "x",
+            "--var",
+            "region=eu-west-1",
+            "--var",
+            "query=a=b",
+        ]);
+        assert_eq!(
+            args.vars,
+            vec![
+                ("region".to_string(), "eu-west-1".to_string()),
+                ("query".to_string(), "a=b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn given_answer_with_spaces_in_label_when_parsing_then_keeps_label() {
+        let args = Cli::parse_from(["rsnip", "copy", "--input", "x", "--answer", "Ticket id=ABC-1"]);
+        assert_eq!(
+            args.answers,
+            vec![("Ticket id".to_string(), "ABC-1".to_string())]
+        );
+    }
+
+    #[test]
+    fn given_var_without_equals_when_parsing_then_fails() {
+        assert!(Cli::try_parse_from(["rsnip", "--var", "region", "types"]).is_err());
+    }
+
+    #[test]
+    fn given_scaffold_without_dest_when_parsing_then_uses_current_directory() {
+        let args = Cli::parse_from(["rsnip", "scaffold", "--ctype", "templates", "rust-module"]);
+        match args.command {
+            Some(Commands::Scaffold { ctype, name, dest, force, dry_run }) => {
+                assert_eq!(ctype.as_deref(), Some("templates"));
+                assert_eq!(name, "rust-module");
+                assert_eq!(dest, PathBuf::from("."));
+                assert!(!force && !dry_run);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn given_expand_with_json_format_when_parsing_then_sets_format() {
+        let args = Cli::parse_from(["rsnip", "expand", "--input", "gc", "--format", "json"]);
+        match args.command {
+            Some(Commands::Expand { format, .. }) => assert_eq!(format, ExpandFormat::Json),
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn given_pane_without_target_when_parsing_then_fails() {
+        assert!(Cli::try_parse_from(["rsnip", "copy", "--input", "gc", "--pane", "%3"]).is_err());
+        assert!(Cli::try_parse_from([
+            "rsnip", "copy", "--input", "gc", "--to", "tmux", "--pane", "%3"
+        ])
+        .is_ok());
+    }
+
+    #[test]
+    fn given_generate_tmux_with_shell_when_parsing_then_fails() {
+        assert!(Cli::parse_from(["rsnip", "--generate-tmux"]).generate_tmux);
+        assert!(Cli::try_parse_from(["rsnip", "--generate-tmux", "--generate", "zsh"]).is_err());
+    }
+
+    #[test]
+    fn given_invalid_now_when_parsing_then_fails() {
+        assert!(Cli::try_parse_from(["rsnip", "--now", "yesterday", "types"]).is_err());
+    }
 }
@@ -1,23 +1,105 @@
+use crate::application::services::{EditorService, EngineFactory, SnippetLibrary, SnippetManagementService};
 use crate::application::snippet_service::SnippetService;
-use crate::cli::args::{Cli, Commands};
+use crate::cli::args::{Cli, Commands, ExpandFormat, OutputTarget};
 use crate::config::{get_snippet_type, Settings, SnippetTypeConfig};
+use crate::domain::menu::{resolve_selection, MenuEntry};
+use crate::domain::template::interface::TemplateEngine;
+use crate::infrastructure::dates::{Clock, TemplateTimezone};
 use crate::infrastructure::edit_snippets::{edit_snips_file, find_snippet_line_number};
+use crate::infrastructure::env_vars::{EnvPolicy, DEFAULT_ENV_DENYLIST};
+use crate::infrastructure::lsp::serve_stdio;
+use crate::infrastructure::menu::{dmenu_lines, rofi_rows, run_dmenu, ERROR_MARK};
 use crate::infrastructure::minijinja::{MiniJinjaEngine, SafeShellExecutor};
+use crate::infrastructure::tmux::Tmux;
 use crate::util::path_utils::expand_path;
 use anyhow::{anyhow, Result};
 use crossterm::style::Stylize;
 use itertools::Itertools;
 use std::fs;
+use std::sync::Arc;
 use dialoguer::Select;
 use dialoguer::theme::ColorfulTheme;
 use tracing::debug;
 
+/// Create the template engine configured from settings and global CLI options
+fn create_template_engine(cli: &Cli, config: &Settings) -> Result<MiniJinjaEngine> {
+    // Unqualified snippet references resolve against the type of the current command
+    let default_type = match &cli.command {
+        Some(Commands::List { ctype, .. })
+        | Some(Commands::Edit { ctype, .. })
+        | Some(Commands::Complete { ctype, .. })
+        | Some(Commands::Copy { ctype, .. })
+        | Some(Commands::Expand { ctype, .. })
+        | Some(Commands::Check { ctype })
+        | Some(Commands::Scaffold { ctype, .. })
+        | Some(Commands::Menu { ctype, .. })
+        | Some(Commands::Lsp { ctype }) => ctype.as_deref(),
+        _ => None,
+    }
+    .unwrap_or("default");
+    create_type_template_engine(cli, config, default_type)
+}
+
+/// Create the template engine resolving unqualified snippet references against `default_type`,
+/// e.g. the type of a snippet picked in a menu
+fn create_type_template_engine(
+    cli: &Cli,
+    config: &Settings,
+    default_type: &str,
+) -> Result<MiniJinjaEngine> {
+    let timezone = match &config.timezone {
+        Some(tz) => tz.parse::<TemplateTimezone>().map_err(|e| anyhow!(e))?,
+        None => TemplateTimezone::default(),
+    };
+    let mut clock = Clock::new(timezone);
+    if let Some(now) = cli.now {
+        clock = clock.with_fixed_now(now);
+    }
+
+    let library = SnippetLibrary::new(config.clone(), default_type);
+
+    let cli_vars = cli
+        .vars
+        .iter()
+        .map(|(key, value)| (key.clone(), serde_json::Value::from(value.as_str())));
+
+    let denylist = config
+        .env_denylist
+        .clone()
+        .unwrap_or_else(|| DEFAULT_ENV_DENYLIST.iter().map(|p| p.to_string()).collect());
+    let env_policy = EnvPolicy::new(config.env_allowlist.clone(), denylist);
+
+    let mut engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()))
+        .with_clock(clock)
+        .with_env_policy(env_policy)
+        .with_legacy_env_vars(config.legacy_env_vars)
+        .with_snippet_resolver(Arc::new(library))
+        .with_variable_resolver(Arc::new(config.clone()))
+        .with_vars(cli_vars)
+        .with_answers(cli.answers.iter().cloned());
+    if let Some(seed) = cli.seed {
+        engine = engine.with_seed(seed);
+    }
+
+    // A broken library is reported once here instead of failing every snippet
+    if let Some(library) = &config.template_library {
+        if let Err(e) = engine.load_template_library(library) {
+            eprintln!("{}", format!("Warning: {}", e).yellow());
+        }
+    }
+    Ok(engine)
+}
+
 pub fn execute_command(cli: &Cli, config: &Settings) -> Result<()> {
-    let template_engine = Box::new(MiniJinjaEngine::new(Box::new(SafeShellExecutor::new())));
+    let template_engine = Box::new(create_template_engine(cli, config)?);
     let service = SnippetService::new(template_engine, config);
 
     match &cli.command {
-        Some(Commands::List { ctype, prefix }) => {
+        Some(Commands::List {
+            ctype,
+            prefix,
+            names_only,
+        }) => {
             let ctype = ctype.as_deref().unwrap_or("default");
             let mut snippets = service.get_snippets(ctype)?;
 
@@ -29,6 +111,13 @@ pub fn execute_command(cli: &Cli, config: &Settings) -> Result<()> {
             // Sort snippets by name
             snippets.sort_by(|a, b| a.name.cmp(&b.name));
 
+            if *names_only {
+                for snippet in snippets {
+                    println!("{}", snippet.name);
+                }
+                return Ok(());
+            }
+
             // Find the longest name for padding
             let max_name_len = snippets.iter().map(|s| s.name.len()).max().unwrap_or(0);
             debug!("Max name length: {}", max_name_len);
@@ -199,10 +288,39 @@ pub fn execute_command(cli: &Cli, config: &Settings) -> Result<()> {
             }
             Ok(())
         }
-        Some(Commands::Copy { ctype, input }) => {
+        Some(Commands::Copy {
+            ctype,
+            input,
+            to,
+            pane,
+        }) => {
             let ctype = ctype.as_deref().unwrap_or("default");
+            if pane.is_some() && *to != OutputTarget::Tmux {
+                return Err(anyhow!("--pane only applies to --to tmux"));
+            }
 
-            match service.copy_snippet_to_clipboard(ctype, input, true)? {
+            let delivered = match to {
+                OutputTarget::Clipboard => service.copy_snippet_to_clipboard(ctype, input, true)?,
+                OutputTarget::Tmux => service
+                    .render_snippet(ctype, input)?
+                    .map(|(snippet, text)| {
+                        Tmux::new()
+                            .send_to_pane(&text, pane.as_deref())
+                            .map(|_| (snippet, text))
+                    })
+                    .transpose()?,
+                OutputTarget::TmuxBuffer => service
+                    .render_snippet(ctype, input)?
+                    .map(|(snippet, text)| Tmux::new().load_buffer(&text, None).map(|_| (snippet, text)))
+                    .transpose()?,
+            };
+            let destination = match to {
+                OutputTarget::Clipboard => "clipboard",
+                OutputTarget::Tmux => "tmux pane",
+                OutputTarget::TmuxBuffer => "tmux buffer",
+            };
+
+            match delivered {
                 Some((snippet, rendered_content)) => {
                     // only print comments if they exist
                     if !snippet.comments.is_empty() {
@@ -212,13 +330,162 @@ pub fn execute_command(cli: &Cli, config: &Settings) -> Result<()> {
                             snippet.comments.join("\n"),
                         );
                     }
-                    println!("{}", format!("'{}' -> clipboard:", snippet.name).green());
+                    println!("{}", format!("'{}' -> {}:", snippet.name, destination).green());
                     println!("{}", rendered_content);
                     Ok(())
                 }
                 None => Err(anyhow!("No matching snippet found for '{}'", input)),
             }
         }
+        Some(Commands::Expand { ctype, input, format }) => {
+            let ctype = ctype.as_deref().unwrap_or("default");
+
+            match service.expand_snippet(ctype, input)? {
+                Some(expansion) => {
+                    match format {
+                        // First line is the cursor offset in characters so shells can split it off
+                        ExpandFormat::Lines => {
+                            println!("{}", expansion.cursor);
+                            print!("{}", expansion.text);
+                        }
+                        ExpandFormat::Json => {
+                            let (line, column) = expansion.line_column();
+                            let json = serde_json::json!({
+                                "name": input,
+                                "text": expansion.text,
+                                "cursor": expansion.cursor,
+                                "line": line,
+                                "column": column,
+                            });
+                            println!("{}", json);
+                        }
+                        ExpandFormat::Text => println!("{}", expansion.text),
+                    }
+                    Ok(())
+                }
+                None => Err(anyhow!("No matching snippet found for '{}'", input)),
+            }
+        }
+        Some(Commands::Menu {
+            ctype,
+            dmenu,
+            selection,
+        }) => {
+            let entries = service.menu_entries(ctype.as_deref())?;
+
+            match dmenu {
+                // Show the menu again with the error on top until a snippet is copied,
+                // giving up when the same entry fails twice
+                Some(command) => {
+                    let mut failed: Option<(String, anyhow::Error)> = None;
+                    loop {
+                        let error = failed.as_ref().map(|(_, e)| format!("{:#}", e));
+                        let Some(selected) =
+                            run_dmenu(command, &dmenu_lines(&entries, error.as_deref()))?
+                        else {
+                            return Ok(());
+                        };
+                        if let Some(message) = selected.strip_prefix(ERROR_MARK) {
+                            return Err(anyhow!(message.to_string()));
+                        }
+                        match copy_menu_selection(cli, config, &entries, &selected) {
+                            Ok(()) => return Ok(()),
+                            Err(e) => match failed {
+                                Some((last, _)) if last == selected => return Err(e),
+                                _ => failed = Some((selected, e)),
+                            },
+                        }
+                    }
+                }
+                // rofi script mode: list entries, or copy the one rofi passes back and close
+                None => {
+                    let Some(selection) = selection else {
+                        print!("{}", rofi_rows(&entries, None));
+                        return Ok(());
+                    };
+                    let selection = std::env::var("ROFI_INFO")
+                        .ok()
+                        .filter(|info| !info.is_empty())
+                        .unwrap_or_else(|| selection.clone());
+                    if let Err(e) = copy_menu_selection(cli, config, &entries, &selection) {
+                        print!("{}", rofi_rows(&entries, Some(&format!("{:#}", e))));
+                    }
+                    Ok(())
+                }
+            }
+        }
+        Some(Commands::Check { ctype }) => {
+            let diagnostics = service.check_snippets(ctype.as_deref())?;
+            for diagnostic in &diagnostics {
+                println!("{}", diagnostic);
+            }
+
+            if diagnostics.is_empty() {
+                eprintln!("{}", "No problems found".green());
+                Ok(())
+            } else {
+                Err(anyhow!("{} problem(s) found", diagnostics.len()))
+            }
+        }
+        Some(Commands::Scaffold {
+            ctype,
+            name,
+            dest,
+            force,
+            dry_run,
+        }) => {
+            let ctype = ctype.as_deref().unwrap_or("default");
+            let plan = service.plan_scaffold(ctype, name, &expand_path(dest)?)?;
+            print!("{}", plan.tree());
+
+            if *dry_run {
+                eprintln!("{}", "Dry run, nothing written".yellow());
+                return Ok(());
+            }
+            plan.write(*force)?;
+            eprintln!(
+                "{}",
+                format!("Created {} entries in {}", plan.entries.len(), plan.dest.display()).green()
+            );
+            Ok(())
+        }
+        Some(Commands::Lsp { ctype }) => {
+            // Each snippet renders with the template engine of its own type
+            let engine_factory: EngineFactory = Box::new(|ctype: &str| {
+                let engine: Box<dyn TemplateEngine> =
+                    Box::new(create_type_template_engine(cli, config, ctype)?);
+                Ok(engine)
+            });
+            let mut editor = EditorService::new(config, engine_factory);
+            if let Some(ctype) = ctype {
+                editor = editor.with_snippet_type(ctype.as_str());
+            }
+            serve_stdio(&editor)
+        }
         None => Ok(()),
     }
 }
+
+/// Render and copy the snippet selected in a menu, with the template engine of its type
+fn copy_menu_selection(
+    cli: &Cli,
+    config: &Settings,
+    entries: &[MenuEntry],
+    selection: &str,
+) -> Result<()> {
+    let (ctype, name) = resolve_selection(entries, selection)
+        .ok_or_else(|| anyhow!("Not a snippet: '{}', expected type/name", selection))?;
+    let not_found = || anyhow!("No matching snippet found for '{}/{}'", ctype, name);
+
+    // A snippet listed under a combined type renders as one of its source type
+    let (source_type, _) = SnippetManagementService::new(config)
+        .get_typed_snippets(&ctype)?
+        .into_iter()
+        .find(|(_, snippet)| snippet.name == name)
+        .ok_or_else(not_found)?;
+    let template_engine = Box::new(create_type_template_engine(cli, config, &source_type)?);
+    SnippetService::new(template_engine, config)
+        .copy_snippet_to_clipboard(&source_type, &name, true)?
+        .ok_or_else(not_found)?;
+    Ok(())
+}
@@ -1,17 +1,44 @@
+use crate::domain::output::{OutputTransform, DEFAULT_OUTPUT};
 use crate::domain::parser::{SnippetFormat, SnippetType};
+use crate::domain::template::interface::VariableResolver;
 use crate::util::path_utils::expand_path;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use std::path::PathBuf;
 use tracing::{debug, instrument, trace};
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub struct Settings {
     #[serde(default)]
     pub snippet_types: HashMap<String, SnippetTypeConfig>,
     #[serde(default = "default_config_paths")]
     pub config_paths: Vec<PathBuf>,
+    /// Timezone for template dates, e.g. "Europe/Berlin"; defaults to the local timezone
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Jinja file with shared macros available in every snippet
+    #[serde(default)]
+    pub template_library: Option<PathBuf>,
+    /// Template variables available to all snippet types
+    #[serde(default)]
+    pub vars: HashMap<String, serde_json::Value>,
+    /// Environment variables templates may read, overriding the denylist; empty allows all
+    #[serde(default)]
+    pub env_allowlist: Vec<String>,
+    /// Environment variables hidden from templates; defaults to common secret patterns
+    #[serde(default)]
+    pub env_denylist: Option<Vec<String>>,
+    /// Expose environment variables as `env_NAME` as before `env()` existed
+    #[serde(default)]
+    pub legacy_env_vars: bool,
+    /// Syntax highlighting theme for previews, "none" to disable
+    #[serde(default)]
+    pub preview_theme: Option<String>,
+    /// Generate shell functions instead of aliases in `--generate` scripts
+    #[serde(default)]
+    pub shell_functions: bool,
     // Track which config file is active
     #[serde(skip)]
     pub active_config_path: Option<PathBuf>,
@@ -28,6 +55,15 @@ pub enum SnippetTypeConfig {
         alias: Option<String>,
         #[serde(default = "default_format")]
         format: String,
+        /// Template variables for this type, overriding global `vars`
+        #[serde(default)]
+        vars: HashMap<String, serde_json::Value>,
+        /// Transforms applied to rendered snippets, e.g. `["dedent", "trim"]`
+        #[serde(default)]
+        output: Option<Vec<OutputTransform>>,
+        /// Shell aliases per action, overriding the ones derived from `alias`
+        #[serde(default, flatten)]
+        aliases: AliasConfig,
     },
     Combined {
         sources: Vec<String>,
@@ -35,9 +71,97 @@ pub enum SnippetTypeConfig {
         description: Option<String>,
         #[serde(default)]
         alias: Option<String>,
+        #[serde(default)]
+        vars: HashMap<String, serde_json::Value>,
+        #[serde(default)]
+        output: Option<Vec<OutputTransform>>,
+        #[serde(default, flatten)]
+        aliases: AliasConfig,
     },
 }
 
+/// Shell alias names per action of a snippet type; an empty name disables the alias
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AliasConfig {
+    /// Copy a snippet, defaults to `alias`
+    #[serde(default)]
+    pub copy_alias: Option<String>,
+    /// Edit a snippet, defaults to `alias` prefixed with `e`
+    #[serde(default)]
+    pub edit_alias: Option<String>,
+    /// Run the rendered snippet in the current shell
+    #[serde(default)]
+    pub exec_alias: Option<String>,
+    /// Print the rendered snippet
+    #[serde(default)]
+    pub render_alias: Option<String>,
+}
+
+/// What a generated shell alias does with the snippet it is given
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AliasAction {
+    Copy,
+    Edit,
+    Exec,
+    Render,
+}
+
+impl AliasAction {
+    /// `rsnip` arguments running the action, before `--ctype` and `--input`
+    pub fn args(self) -> &'static str {
+        match self {
+            AliasAction::Copy => "copy",
+            AliasAction::Edit => "edit",
+            AliasAction::Exec | AliasAction::Render => "expand --format text",
+        }
+    }
+
+    /// Verb describing the action, e.g. in generated help texts
+    pub fn verb(self) -> &'static str {
+        match self {
+            AliasAction::Copy => "Copy",
+            AliasAction::Edit => "Edit",
+            AliasAction::Exec => "Run",
+            AliasAction::Render => "Print",
+        }
+    }
+}
+
+impl fmt::Display for AliasAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::String(name)) => write!(f, "{}", name),
+            _ => Err(fmt::Error),
+        }
+    }
+}
+
+impl SnippetTypeConfig {
+    /// Shell aliases of this type with their actions, in the order copy, edit, exec, render
+    pub fn shell_aliases(&self) -> Vec<(AliasAction, String)> {
+        let (alias, aliases) = match self {
+            SnippetTypeConfig::Concrete { alias, aliases, .. }
+            | SnippetTypeConfig::Combined { alias, aliases, .. } => (alias, aliases),
+        };
+        [
+            (AliasAction::Copy, aliases.copy_alias.clone().or_else(|| alias.clone())),
+            (
+                AliasAction::Edit,
+                aliases
+                    .edit_alias
+                    .clone()
+                    .or_else(|| alias.as_ref().map(|alias| format!("e{}", alias))),
+            ),
+            (AliasAction::Exec, aliases.exec_alias.clone()),
+            (AliasAction::Render, aliases.render_alias.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(action, name)| name.filter(|name| !name.is_empty()).map(|name| (action, name)))
+        .collect()
+    }
+}
+
 fn default_format() -> String {
     "default".to_string()
 }
@@ -62,6 +186,9 @@ fn default_snippet_types() -> HashMap<String, SnippetTypeConfig> {
             description: Some("Default snippet type".to_string()),
             alias: None,
             format: "default".to_string(),
+            vars: HashMap::new(),
+            output: None,
+            aliases: AliasConfig::default(),
         },
     );
     types
@@ -72,6 +199,14 @@ impl Default for Settings {
         Self {
             snippet_types: default_snippet_types(),
             config_paths: default_config_paths(),
+            timezone: None,
+            template_library: None,
+            vars: HashMap::new(),
+            env_allowlist: Vec::new(),
+            env_denylist: None,
+            legacy_env_vars: false,
+            preview_theme: None,
+            shell_functions: false,
             active_config_path: None,
         }
     }
@@ -116,6 +251,10 @@ impl Settings {
         // Store the active config path
         settings.active_config_path = active_path;
 
+        if let Some(library) = &mut settings.template_library {
+            *library = expand_path(&library)?;
+        }
+
         // Expand paths in concrete snippet types
         for config in settings.snippet_types.values_mut() {
             if let SnippetTypeConfig::Concrete { source_file, .. } = config {
@@ -153,6 +292,34 @@ impl Settings {
             _ => None,
         }
     }
+
+    /// Template variables for a snippet type: global `vars` overridden by the type's `vars`
+    pub fn template_vars(&self, name: &str) -> HashMap<String, serde_json::Value> {
+        let mut vars = self.vars.clone();
+        match self.snippet_types.get(name) {
+            Some(SnippetTypeConfig::Concrete { vars: type_vars, .. })
+            | Some(SnippetTypeConfig::Combined { vars: type_vars, .. }) => {
+                vars.extend(type_vars.clone());
+            }
+            None => {}
+        }
+        vars
+    }
+
+    /// Output pipeline of a snippet type, `DEFAULT_OUTPUT` if it configures none
+    pub fn output_transforms(&self, name: &str) -> Vec<OutputTransform> {
+        match self.snippet_types.get(name) {
+            Some(SnippetTypeConfig::Concrete { output: Some(output), .. })
+            | Some(SnippetTypeConfig::Combined { output: Some(output), .. }) => output.clone(),
+            _ => DEFAULT_OUTPUT.to_vec(),
+        }
+    }
+}
+
+impl VariableResolver for Settings {
+    fn vars(&self, snippet_type: &str) -> HashMap<String, serde_json::Value> {
+        self.template_vars(snippet_type)
+    }
 }
 
 #[instrument(level = "debug")]
@@ -177,13 +344,16 @@ mod tests {
                 description: None,
                 alias: None,
                 format: "default".to_string(),
+                vars: HashMap::new(),
+                output: None,
+                aliases: AliasConfig::default(),
             },
         );
 
         let settings = Settings {
             snippet_types,
             config_paths: vec![],
-            active_config_path: None,
+            ..Default::default()
         };
 
         let snippet_type = settings.get_snippet_type("test");
@@ -203,13 +373,16 @@ mod tests {
                 sources: vec!["source1".to_string(), "source2".to_string()],
                 description: None,
                 alias: None,
+                vars: HashMap::new(),
+                output: None,
+                aliases: AliasConfig::default(),
             },
         );
 
         let settings = Settings {
             snippet_types,
             config_paths: vec![],
-            active_config_path: None,
+            ..Default::default()
         };
 
         assert!(settings.get_snippet_type("combined").is_none());
@@ -225,13 +398,16 @@ mod tests {
                 sources: sources.clone(),
                 description: None,
                 alias: None,
+                vars: HashMap::new(),
+                output: None,
+                aliases: AliasConfig::default(),
             },
         );
 
         let settings = Settings {
             snippet_types,
             config_paths: vec![],
-            active_config_path: None,
+            ..Default::default()
         };
 
         let result = settings.get_combined_sources("combined");
@@ -249,15 +425,101 @@ mod tests {
                 description: None,
                 alias: None,
                 format: "default".to_string(),
+                vars: HashMap::new(),
+                output: None,
+                aliases: AliasConfig::default(),
             },
         );
 
         let settings = Settings {
             snippet_types,
             config_paths: vec![],
-            active_config_path: None,
+            ..Default::default()
         };
 
         assert!(settings.get_combined_sources("test").is_none());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn given_global_and_type_vars_when_getting_template_vars_then_type_overrides_global() {
+        let settings: Settings = toml::from_str(
+            r#"
+            [vars]
+            team_email = "team@example.com"
+            default_region = "eu-central-1"
+
+            [snippet_types.aws]
+            source_file = "aws.txt"
+            vars = { default_region = "us-east-1", ports = [80, 443] }
+            "#,
+        )
+        .unwrap();
+
+        let vars = settings.template_vars("aws");
+
+        assert_eq!(vars["team_email"], "team@example.com");
+        assert_eq!(vars["default_region"], "us-east-1");
+        assert_eq!(vars["ports"], serde_json::json!([80, 443]));
+        assert_eq!(
+            settings.template_vars("unknown")["default_region"],
+            "eu-central-1"
+        );
+    }
+
+    #[test]
+    fn given_type_output_when_getting_output_transforms_then_falls_back_to_trim() {
+        let settings: Settings = toml::from_str(
+            r#"
+            [snippet_types.json]
+            source_file = "json.txt"
+            output = ["dedent", "json_escape"]
+
+            [snippet_types.plain]
+            source_file = "plain.txt"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            settings.output_transforms("json"),
+            vec![OutputTransform::Dedent, OutputTransform::JsonEscape]
+        );
+        assert_eq!(settings.output_transforms("plain"), vec![OutputTransform::Trim]);
+    }
+
+    #[test]
+    fn given_alias_and_action_aliases_when_getting_shell_aliases_then_overrides_defaults() {
+        let settings: Settings = toml::from_str(
+            r#"
+            [snippet_types.shell]
+            source_file = "shell.txt"
+            alias = ","
+            render_alias = "p,"
+
+            [snippet_types.combined]
+            sources = ["shell"]
+            alias = ",,"
+            edit_alias = ""
+            exec_alias = "x,"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            settings.snippet_types["shell"].shell_aliases(),
+            vec![
+                (AliasAction::Copy, ",".to_string()),
+                (AliasAction::Edit, "e,".to_string()),
+                (AliasAction::Render, "p,".to_string()),
+            ]
+        );
+        assert_eq!(
+            settings.snippet_types["combined"].shell_aliases(),
+            vec![
+                (AliasAction::Copy, ",,".to_string()),
+                (AliasAction::Exec, "x,".to_string()),
+            ]
+        );
+        assert_eq!(AliasAction::Exec.to_string(), "exec");
+    }
+}
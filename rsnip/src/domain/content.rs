@@ -1,25 +1,10 @@
-use minijinja::Template;
 use tracing::{instrument, trace};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum SnippetContent {
     Static(String),
-    Template {
-        source: String,
-        // We don't clone the compiled template, just store it for performance
-        compiled: Option<Template<'static, 'static>>,
-    },
-}
-
-// Manual implementation of PartialEq that ignores the compiled template
-impl PartialEq for SnippetContent {
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Self::Static(a), Self::Static(b)) => a == b,
-            (Self::Template { source: a, .. }, Self::Template { source: b, .. }) => a == b,
-            _ => false,
-        }
-    }
+    /// Compiled by the template engine when rendered, which caches it by source
+    Template { source: String },
 }
 
 // Implement template detection
@@ -27,12 +12,11 @@ impl SnippetContent {
     #[instrument(level = "debug")]
     pub fn new(content: String) -> Self {
         trace!("SnippetContent::new called with: {}", content);
-        if content.contains("{{") && content.contains("}}") {
+        let has_expression = content.contains("{{") && content.contains("}}");
+        let has_statement = content.contains("{%") && content.contains("%}");
+        if has_expression || has_statement {
             trace!("Detected as template");
-            SnippetContent::Template {
-                source: content,
-                compiled: None,
-            }
+            SnippetContent::Template { source: content }
         } else {
             trace!("Detected as static");
             SnippetContent::Static(content)
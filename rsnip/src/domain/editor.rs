@@ -0,0 +1,77 @@
+// domain/editor.rs
+use anyhow::Result;
+
+/// Snippet offered as a completion in an editor
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EditorSnippet {
+    pub ctype: String,
+    pub name: String,
+    /// First comment line of the snippet
+    pub description: Option<String>,
+    /// Language the snippet is scoped to, offered in all documents if `None`
+    pub language: Option<String>,
+    /// Snippet source as written in the snippet file
+    pub source: String,
+    /// Text to insert in LSP snippet syntax; `None` for templates until resolved
+    pub insert_text: Option<String>,
+}
+
+/// Supplies the snippets an editor completes, e.g. through the language server
+pub trait SnippetProvider {
+    /// Snippets for a document in `language`, all snippets if the language is unknown
+    fn snippets(&self, language: Option<&str>) -> Result<Vec<EditorSnippet>>;
+
+    /// Render the snippet `name` of `ctype` into text to insert in LSP snippet syntax
+    fn resolve(&self, ctype: &str, name: &str) -> Result<String>;
+}
+
+/// File extensions and scope names with the LSP language id they stand for
+const LANGUAGE_IDS: &[(&str, &str)] = &[
+    ("sh", "shellscript"),
+    ("bash", "shellscript"),
+    ("zsh", "shellscript"),
+    ("shell", "shellscript"),
+    ("py", "python"),
+    ("rs", "rust"),
+    ("js", "javascript"),
+    ("ts", "typescript"),
+    ("jsx", "javascriptreact"),
+    ("tsx", "typescriptreact"),
+    ("rb", "ruby"),
+    ("md", "markdown"),
+    ("yml", "yaml"),
+    ("cs", "csharp"),
+    ("kt", "kotlin"),
+    ("tf", "terraform"),
+];
+
+/// LSP language id for a snippet language given as id, file extension or scope name
+pub fn language_id(language: &str) -> String {
+    let language = language.trim().to_lowercase();
+    LANGUAGE_IDS
+        .iter()
+        .find(|(alias, _)| *alias == language)
+        .map_or(language, |(_, id)| id.to_string())
+}
+
+/// Whether a snippet in `snippet_language` is offered in a document in `document_language`
+pub fn language_matches(snippet_language: Option<&str>, document_language: Option<&str>) -> bool {
+    match (snippet_language, document_language) {
+        (Some(snippet), Some(document)) => language_id(snippet) == language_id(document),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_extension_or_language_id_when_matching_then_compares_language_ids() {
+        assert!(language_matches(Some("sh"), Some("shellscript")));
+        assert!(language_matches(Some("Rust"), Some("rust")));
+        assert!(language_matches(None, Some("python")));
+        assert!(language_matches(Some("python"), None));
+        assert!(!language_matches(Some("py"), Some("shellscript")));
+    }
+}
@@ -0,0 +1,137 @@
+// domain/manifest.rs
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+/// File or directory produced by a file-template snippet
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManifestEntry {
+    /// Path relative to the destination directory
+    pub path: PathBuf,
+    /// File content, `None` for a directory
+    pub content: Option<String>,
+}
+
+/// Path of a section header line `== path: src/main.rs ==`
+fn section_path(line: &str) -> Option<&str> {
+    line.trim()
+        .strip_prefix("== path:")
+        .and_then(|rest| rest.strip_suffix("=="))
+        .map(str::trim)
+}
+
+/// Split rendered output into `== path: ... ==` sections. A path ending in `/` is
+/// a directory; file contents end with exactly one newline.
+pub fn parse_manifest(text: &str) -> Result<Vec<ManifestEntry>, String> {
+    let mut entries = Vec::new();
+    let mut seen = HashSet::new();
+    let mut current: Option<(String, Vec<&str>)> = None;
+
+    for (idx, line) in text.lines().enumerate() {
+        if let Some(path) = section_path(line) {
+            if let Some((path, lines)) = current.take() {
+                entries.push(entry(&path, &lines)?);
+            }
+            if !seen.insert(path.trim_end_matches('/').to_string()) {
+                return Err(format!("Duplicate path '{}' in line {}", path, idx + 1));
+            }
+            current = Some((path.to_string(), Vec::new()));
+        } else if let Some((_, lines)) = current.as_mut() {
+            lines.push(line);
+        } else if !line.trim().is_empty() {
+            return Err(format!(
+                "Text outside of a '== path: ... ==' section in line {}",
+                idx + 1
+            ));
+        }
+    }
+    if let Some((path, lines)) = current {
+        entries.push(entry(&path, &lines)?);
+    }
+
+    if entries.is_empty() {
+        return Err("No '== path: ... ==' sections found".to_string());
+    }
+    Ok(entries)
+}
+
+fn entry(path: &str, lines: &[&str]) -> Result<ManifestEntry, String> {
+    let relative = Path::new(path.trim_end_matches('/'));
+    let is_safe = relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if path.is_empty() || relative.as_os_str().is_empty() || !is_safe {
+        return Err(format!(
+            "Invalid path '{}': paths must be relative and stay inside the destination",
+            path
+        ));
+    }
+
+    if path.ends_with('/') {
+        if lines.iter().any(|line| !line.trim().is_empty()) {
+            return Err(format!("Directory '{}' cannot have content", path));
+        }
+        return Ok(ManifestEntry {
+            path: relative.to_path_buf(),
+            content: None,
+        });
+    }
+
+    let content = lines.join("\n");
+    let content = content.trim_matches('\n');
+    Ok(ManifestEntry {
+        path: relative.to_path_buf(),
+        content: Some(if content.is_empty() {
+            String::new()
+        } else {
+            format!("{}\n", content)
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_sections_when_parsing_then_returns_files_and_directories() {
+        let text = "== path: src/lib.rs ==\npub mod parser;\n\n== path: logs/ ==\n== path: src/parser.rs ==\n\nfn parse() {}\n\n";
+
+        let entries = parse_manifest(text).unwrap();
+
+        assert_eq!(
+            entries,
+            vec![
+                ManifestEntry {
+                    path: PathBuf::from("src/lib.rs"),
+                    content: Some("pub mod parser;\n".to_string()),
+                },
+                ManifestEntry {
+                    path: PathBuf::from("logs"),
+                    content: None,
+                },
+                ManifestEntry {
+                    path: PathBuf::from("src/parser.rs"),
+                    content: Some("fn parse() {}\n".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn given_escaping_paths_when_parsing_then_fails() {
+        assert!(parse_manifest("== path: ../evil ==\nx").is_err());
+        assert!(parse_manifest("== path: /etc/passwd ==\nx").is_err());
+        assert!(parse_manifest("== path: a/../../b ==\nx").is_err());
+    }
+
+    #[test]
+    fn given_invalid_layout_when_parsing_then_reports_problem() {
+        assert!(parse_manifest("stray\n== path: a ==\nx")
+            .unwrap_err()
+            .contains("line 1"));
+        assert!(parse_manifest("== path: a ==\n== path: a ==\n")
+            .unwrap_err()
+            .contains("Duplicate"));
+        assert!(parse_manifest("plain text").is_err());
+    }
+}
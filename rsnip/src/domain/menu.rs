@@ -0,0 +1,64 @@
+// domain/menu.rs
+
+/// Snippet offered in a launcher menu such as rofi, dmenu or fuzzel
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MenuEntry {
+    pub ctype: String,
+    pub name: String,
+    /// First comment line of the snippet
+    pub description: Option<String>,
+}
+
+impl MenuEntry {
+    /// `type/name`, identifying the snippet across types
+    pub fn label(&self) -> String {
+        format!("{}/{}", self.ctype, self.name)
+    }
+
+    /// Label followed by the description, as shown in the menu
+    pub fn display(&self) -> String {
+        match &self.description {
+            Some(description) => format!("{}  {}", self.label(), description),
+            None => self.label(),
+        }
+    }
+}
+
+/// Snippet type and name of a menu selection: the display text or label of an entry,
+/// or a `type/name` typed by the user
+pub fn resolve_selection(entries: &[MenuEntry], selection: &str) -> Option<(String, String)> {
+    let selection = selection.trim_end_matches('\n');
+    if let Some(entry) = entries
+        .iter()
+        .find(|entry| entry.display() == selection || entry.label() == selection)
+    {
+        return Some((entry.ctype.clone(), entry.name.clone()));
+    }
+    selection
+        .split_once('/')
+        .filter(|(ctype, name)| !ctype.is_empty() && !name.is_empty())
+        .map(|(ctype, name)| (ctype.to_string(), name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_display_label_or_typed_text_when_resolving_then_returns_type_and_name() {
+        let entries = vec![MenuEntry {
+            ctype: "shell".to_string(),
+            name: "docker/run".to_string(),
+            description: Some("Run a container".to_string()),
+        }];
+        let expected = Some(("shell".to_string(), "docker/run".to_string()));
+
+        assert_eq!(resolve_selection(&entries, "shell/docker/run  Run a container"), expected);
+        assert_eq!(resolve_selection(&entries, "shell/docker/run\n"), expected);
+        assert_eq!(
+            resolve_selection(&entries, "rust/main"),
+            Some(("rust".to_string(), "main".to_string()))
+        );
+        assert_eq!(resolve_selection(&entries, "main"), None);
+    }
+}
@@ -1,5 +1,9 @@
 pub mod parser;
 pub mod snippet;
 pub mod content;
+pub mod manifest;
+pub mod editor;
+pub mod menu;
+pub mod output;
 pub mod template;
 pub(crate) mod errors;
\ No newline at end of file
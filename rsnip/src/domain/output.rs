@@ -0,0 +1,350 @@
+// domain/output.rs
+use serde::de::value::StrDeserializer;
+use serde::de::IntoDeserializer;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Step of the pipeline shaping rendered output before it is copied or printed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputTransform {
+    /// Strip trailing newlines
+    Trim,
+    /// End with exactly one newline
+    Newline,
+    /// Remove indentation common to all non-blank lines
+    Dedent,
+    /// Join lines into one, dropping blank lines and `\` line continuations
+    SingleLine,
+    /// Quote as one shell word
+    ShellQuote,
+    /// Escape as the inside of a JSON string
+    JsonEscape,
+}
+
+/// Pipeline used when neither the snippet nor its type configures one
+pub const DEFAULT_OUTPUT: &[OutputTransform] = &[OutputTransform::Trim];
+
+/// Marks where the cursor goes after rendering, also as `${0}` or `${0:placeholder}`
+/// like in VS Code snippets; `\$0` is a literal `$0`
+pub const CURSOR_MARKER: &str = "$0";
+
+/// Stands in for the cursor marker while output transforms run
+const CURSOR_SENTINEL: char = '\u{E000}';
+
+/// Rendered snippet text with the cursor position, in characters from the start
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Expansion {
+    pub text: String,
+    pub cursor: usize,
+}
+
+impl Expansion {
+    /// Shape `rendered` with `transforms`, keeping track of its cursor marker
+    pub fn render(rendered: &str, transforms: &[OutputTransform]) -> Result<Self, String> {
+        Ok(Self::from_sentinel(&apply_output(
+            &mark_cursor(rendered)?,
+            transforms,
+        )))
+    }
+
+    /// Remove the first cursor marker from `text`; without one the cursor goes to the end
+    pub fn from_marked(text: &str) -> Result<Self, String> {
+        Ok(Self::from_sentinel(&mark_cursor(text)?))
+    }
+
+    fn from_sentinel(text: &str) -> Self {
+        match text.split_once(CURSOR_SENTINEL) {
+            Some((head, tail)) => Self {
+                text: format!("{}{}", head, tail.replace(CURSOR_SENTINEL, "")),
+                cursor: head.chars().count(),
+            },
+            None => Self {
+                text: text.to_string(),
+                cursor: text.chars().count(),
+            },
+        }
+    }
+
+    /// 0-based line and column of the cursor, in characters
+    pub fn line_column(&self) -> (usize, usize) {
+        let before: String = self.text.chars().take(self.cursor).collect();
+        let line = before.matches('\n').count();
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count();
+        (line, column)
+    }
+
+    /// The text in LSP snippet syntax: `$`, `}` and `\` escaped, the cursor as the final tabstop
+    pub fn to_snippet_syntax(&self) -> String {
+        let escape = |text: &str| {
+            text.replace('\\', r"\\")
+                .replace('$', r"\$")
+                .replace('}', r"\}")
+        };
+        let head: String = self.text.chars().take(self.cursor).collect();
+        let tail: String = self.text.chars().skip(self.cursor).collect();
+        if tail.is_empty() {
+            escape(&head)
+        } else {
+            format!("{}$0{}", escape(&head), escape(&tail))
+        }
+    }
+}
+
+/// Replace the first cursor marker with a sentinel that survives output transforms,
+/// keeping a placeholder after it; escaped markers become literal text
+fn mark_cursor(text: &str) -> Result<String, String> {
+    if text.contains(CURSOR_SENTINEL) {
+        return Err(format!(
+            "Snippet contains the reserved character U+{:04X}, which marks the cursor while expanding",
+            CURSOR_SENTINEL as u32
+        ));
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut found = false;
+    let mut rest = text;
+
+    while let Some(idx) = rest.find('$') {
+        let (head, tail) = rest.split_at(idx);
+        let Some((len, placeholder)) = cursor_marker(tail) else {
+            result.push_str(head);
+            result.push('$');
+            rest = &tail[1..];
+            continue;
+        };
+
+        if let Some(head) = head.strip_suffix('\\') {
+            result.push_str(head);
+            result.push_str(&tail[..len]);
+        } else if !found {
+            result.push_str(head);
+            result.push(CURSOR_SENTINEL);
+            result.push_str(placeholder);
+            found = true;
+        } else {
+            result.push_str(head);
+            result.push_str(&tail[..len]);
+        }
+        rest = &tail[len..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Length and placeholder of a cursor marker at the start of `text`
+fn cursor_marker(text: &str) -> Option<(usize, &str)> {
+    if let Some(after) = text.strip_prefix(CURSOR_MARKER) {
+        return (!after.starts_with(|c: char| c.is_ascii_digit())).then_some((2, ""));
+    }
+    if text.starts_with("${0}") {
+        return Some((4, ""));
+    }
+    let placeholder = text.strip_prefix("${0:")?;
+    let end = placeholder.find('}')?;
+    Some((4 + end + 1, &placeholder[..end]))
+}
+
+impl OutputTransform {
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            OutputTransform::Trim => text.trim_end_matches(['\n', '\r']).to_string(),
+            OutputTransform::Newline => format!("{}\n", text.trim_end_matches(['\n', '\r'])),
+            OutputTransform::Dedent => dedent(text),
+            OutputTransform::SingleLine => single_line(text),
+            OutputTransform::ShellQuote => shell_quote(text),
+            OutputTransform::JsonEscape => {
+                let quoted = serde_json::Value::String(text.to_string()).to_string();
+                quoted[1..quoted.len() - 1].to_string()
+            }
+        }
+    }
+}
+
+/// Run `text` through `transforms` in order
+pub fn apply_output(text: &str, transforms: &[OutputTransform]) -> String {
+    transforms
+        .iter()
+        .fold(text.to_string(), |text, transform| transform.apply(&text))
+}
+
+/// Parse a comma-separated pipeline such as `dedent, single_line`
+pub fn parse_output(spec: &str) -> Result<Vec<OutputTransform>, String> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+impl FromStr for OutputTransform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name: StrDeserializer<'_, serde::de::value::Error> = s.into_deserializer();
+        Self::deserialize(name).map_err(|e| format!("Unknown output transform '{}': {}", s, e))
+    }
+}
+
+impl fmt::Display for OutputTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::String(name)) => write!(f, "{}", name),
+            _ => Err(fmt::Error),
+        }
+    }
+}
+
+/// Remove the indentation common to all non-blank lines, keeping a trailing newline
+pub fn dedent(text: &str) -> String {
+    let indent = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let mut dedented: Vec<&str> = text
+        .lines()
+        .map(|line| line.get(indent..).unwrap_or_else(|| line.trim_start()))
+        .collect();
+    if text.ends_with('\n') {
+        dedented.push("");
+    }
+    dedented.join("\n")
+}
+
+/// Quote for POSIX shells, leaving values without special characters untouched
+pub fn shell_quote(text: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "_@%+=:,./-".contains(c);
+    if !text.is_empty() && text.chars().all(is_safe) {
+        text.to_string()
+    } else {
+        format!("'{}'", text.replace('\'', r"'\''"))
+    }
+}
+
+fn single_line(text: &str) -> String {
+    text.lines()
+        .map(|line| line.trim().trim_end_matches('\\').trim_end())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_indented_block_when_dedenting_then_keeps_relative_indentation() {
+        let text = "    if x:\n        y()\n\n    z()\n";
+
+        assert_eq!(OutputTransform::Dedent.apply(text), "if x:\n    y()\n\nz()\n");
+    }
+
+    #[test]
+    fn given_continued_command_when_joining_then_returns_single_line() {
+        let text = "docker run \\\n  --rm \\\n\n  alpine\n";
+
+        assert_eq!(OutputTransform::SingleLine.apply(text), "docker run --rm alpine");
+    }
+
+    #[test]
+    fn given_quotes_when_quoting_or_escaping_then_output_is_safe() {
+        assert_eq!(OutputTransform::ShellQuote.apply("it's"), r"'it'\''s'");
+        assert_eq!(OutputTransform::ShellQuote.apply("a/b.txt"), "a/b.txt");
+        assert_eq!(
+            OutputTransform::JsonEscape.apply("say \"hi\"\n\tnow"),
+            r#"say \"hi\"\n\tnow"#
+        );
+    }
+
+    #[test]
+    fn given_trailing_newlines_when_trimming_or_ensuring_then_normalizes_end() {
+        assert_eq!(OutputTransform::Trim.apply("text\n\n"), "text");
+        assert_eq!(OutputTransform::Newline.apply("text"), "text\n");
+        assert_eq!(OutputTransform::Newline.apply("text\n\n"), "text\n");
+    }
+
+    #[test]
+    fn given_cursor_marker_when_expanding_then_strips_it_and_records_offset() {
+        assert_eq!(
+            Expansion::from_marked("git commit -m \"$0\"").unwrap(),
+            Expansion {
+                text: "git commit -m \"\"".to_string(),
+                cursor: 15,
+            }
+        );
+        assert_eq!(Expansion::from_marked("ls").unwrap().cursor, 2);
+        assert_eq!(Expansion::from_marked("é$0").unwrap().cursor, 1);
+    }
+
+    #[test]
+    fn given_escaped_or_repeated_marker_when_expanding_then_keeps_literal_text() {
+        let expansion = Expansion::from_marked("echo \\$0 $0 $0 $10").unwrap();
+
+        assert_eq!(expansion.text, "echo $0  $0 $10");
+        assert_eq!(expansion.cursor, 8);
+    }
+
+    #[test]
+    fn given_vscode_style_markers_when_expanding_then_keeps_placeholder_after_cursor() {
+        assert_eq!(
+            Expansion::from_marked("fn main() {\n    ${0:todo!()}\n}").unwrap(),
+            Expansion {
+                text: "fn main() {\n    todo!()\n}".to_string(),
+                cursor: 16,
+            }
+        );
+        assert_eq!(Expansion::from_marked("a${0}b").unwrap().cursor, 1);
+        assert_eq!(Expansion::from_marked("\\${0}").unwrap().text, "${0}");
+    }
+
+    #[test]
+    fn given_transforms_when_rendering_then_cursor_follows_the_marker() {
+        let expansion = Expansion::render(
+            "    docker run \\\n      --name $0 \\\n      alpine\n",
+            &[OutputTransform::Dedent, OutputTransform::SingleLine],
+        )
+        .unwrap();
+
+        assert_eq!(expansion.text, "docker run --name  alpine");
+        assert_eq!(expansion.cursor, 18);
+    }
+
+    #[test]
+    fn given_reserved_cursor_character_when_expanding_then_fails() {
+        assert!(Expansion::from_marked("a\u{E000}b $0")
+            .unwrap_err()
+            .contains("U+E000"));
+    }
+
+    #[test]
+    fn given_multiline_text_when_locating_cursor_then_returns_line_and_column() {
+        let expansion = Expansion::from_marked("if x:\n    $0\nend").unwrap();
+
+        assert_eq!(expansion.line_column(), (1, 4));
+    }
+
+    #[test]
+    fn given_shell_text_when_converting_to_snippet_syntax_then_escapes_all_but_cursor() {
+        let expansion = Expansion::from_marked(r#"awk '{print $1}' "$0" \n"#).unwrap();
+
+        assert_eq!(
+            expansion.to_snippet_syntax(),
+            r#"awk '{print \$1\}' "$0" \\n"#
+        );
+        assert_eq!(Expansion::from_marked("echo $HOME").unwrap().to_snippet_syntax(), r"echo \$HOME");
+    }
+
+    #[test]
+    fn given_pipeline_spec_when_parsing_then_applies_in_order() {
+        let pipeline = parse_output("dedent, single_line,shell_quote").unwrap();
+
+        assert_eq!(apply_output("  echo \\\n    hi\n", &pipeline), "'echo hi'");
+        assert!(parse_output("shout").unwrap_err().contains("json_escape"));
+        assert_eq!(OutputTransform::SingleLine.to_string(), "single_line");
+    }
+}
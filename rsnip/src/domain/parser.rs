@@ -18,6 +18,7 @@ pub enum SnippetFormat {
 }
 
 impl SnippetFormat {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(format: &str) -> Option<Self> {
         match format.to_lowercase().as_str() {
             "default" => Some(Self::Default),
@@ -1,9 +1,24 @@
 use crate::domain::content::SnippetContent;
+use crate::domain::output::OutputTransform;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Snippet {
     pub name: String,
     pub content: SnippetContent,
     pub comments: Vec<String>,
+    /// Language of the content for syntax highlighting, e.g. `rust`
+    pub language: Option<String>,
+    /// Output pipeline overriding the one of the snippet type
+    pub output: Option<Vec<OutputTransform>>,
 }
 
+impl Snippet {
+    /// First non-empty comment, shown next to the name in menus and editors
+    pub fn description(&self) -> Option<String> {
+        self.comments
+            .iter()
+            .map(|comment| comment.trim())
+            .find(|comment| !comment.is_empty())
+            .map(str::to_string)
+    }
+}
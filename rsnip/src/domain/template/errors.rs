@@ -11,4 +11,8 @@ pub enum TemplateError {
     Context(String),
     #[error("Shell command error: {0}")]
     Shell(String),
+    #[error("Snippet reference error: {0}")]
+    Reference(String),
+    #[error("Prompt error: {0}")]
+    Prompt(String),
 }
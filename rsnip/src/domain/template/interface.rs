@@ -1,13 +1,54 @@
 // domain/template/interface.rs
 use crate::domain::content::SnippetContent;
 use crate::domain::template::errors::TemplateError;
+use crate::domain::template::model::TemplateIssue;
 use anyhow::Result;
+use std::collections::HashMap;
 
+/// Renders snippet content. `snippet_type` is the type the snippet was looked up in,
+/// which selects the variables of that type.
 pub trait TemplateEngine: Send + Sync {
-    fn render(&self, content: &SnippetContent) -> Result<String, TemplateError>;
+    fn render(&self, snippet_type: &str, content: &SnippetContent) -> Result<String, TemplateError>;
+
+    /// Render for display only, e.g. in a preview pane. Must not ask the user for input;
+    /// engines may reuse side-effect results such as shell output between previews.
+    fn render_preview(&self, snippet_type: &str, content: &SnippetContent) -> Result<String, TemplateError> {
+        self.render(snippet_type, content)
+    }
+
+    /// Report problems in a template without rendering it; engines without checks report none
+    fn check(&self, _snippet_type: &str, _content: &SnippetContent) -> Vec<TemplateIssue> {
+        Vec::new()
+    }
 }
 // in domain/template/interface.rs
 pub trait ShellCommandExecutor: Send + Sync {
     fn execute(&self, command: &str) -> Result<String, TemplateError>;
     fn box_clone(&self) -> Box<dyn ShellCommandExecutor>;
+
+    /// Whether `command` would be allowed to run
+    fn validate(&self, _command: &str) -> Result<(), TemplateError> {
+        Ok(())
+    }
+}
+
+/// Looks up snippets referenced from templates, e.g. `{% include 'shell:header' %}`
+pub trait SnippetResolver: Send + Sync {
+    /// Return the source of the snippet `reference` (`type:name` or `name`), `None` if unknown
+    fn resolve(&self, reference: &str) -> Result<Option<String>, TemplateError>;
+}
+
+/// Asks the user for values requested by `prompt()`, `confirm()` and `choose()` in templates
+pub trait UserPrompter: Send + Sync {
+    /// Whether questions can be asked, e.g. a terminal is attached
+    fn is_interactive(&self) -> bool;
+    fn input(&self, label: &str, default: Option<&str>) -> Result<String, TemplateError>;
+    fn confirm(&self, label: &str, default: bool) -> Result<bool, TemplateError>;
+    /// Return the index of the selected option
+    fn select(&self, label: &str, options: &[String], default: usize) -> Result<usize, TemplateError>;
+}
+
+/// Looks up the user-defined template variables of a snippet type, e.g. from the configuration
+pub trait VariableResolver: Send + Sync {
+    fn vars(&self, snippet_type: &str) -> HashMap<String, serde_json::Value>;
 }
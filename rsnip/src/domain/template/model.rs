@@ -4,7 +4,24 @@ use chrono::{DateTime, Utc};
 
 // todo: delete?
 
-#[derive(Clone, Debug)]
+/// Problem found when checking a template without rendering it
+#[derive(Clone, Debug, PartialEq)]
+pub struct TemplateIssue {
+    /// 1-based line within the template, if known
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl TemplateIssue {
+    pub fn new(line: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct TemplateContext {
     pub(crate) variables: HashMap<String, TemplateValue>,
 }
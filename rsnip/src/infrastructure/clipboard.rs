@@ -5,9 +5,15 @@ use tracing::instrument;
 #[instrument(level = "trace")]
 pub fn copy_to_clipboard(text: &str) -> Result<()> {
     let mut clipboard = Clipboard::new().context("Failed to initialize clipboard")?;
-    let clean_text = text.trim_end_matches('\n');
     clipboard
-        .set_text(clean_text)
+        .set_text(text)
         .context("Failed to set clipboard text")?;
     Ok(())
-}
\ No newline at end of file
+}
+#[instrument(level = "trace")]
+pub fn get_clipboard_text() -> Result<String> {
+    let mut clipboard = Clipboard::new().context("Failed to initialize clipboard")?;
+    clipboard
+        .get_text()
+        .context("Failed to get clipboard text")
+}
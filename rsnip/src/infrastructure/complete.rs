@@ -1,8 +1,9 @@
 // complete.rs
-use crate::config::{Settings, SnippetTypeConfig};
-use anyhow::Result;
+use crate::config::{AliasAction, Settings};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
 use std::io::Write;
-use clap_complete::Shell;
+use clap::{Command, ValueEnum};
 use minijinja::Environment;
 use serde::Serialize;
 use tracing::{debug, instrument};
@@ -11,58 +12,545 @@ use tracing::{debug, instrument};
 #[derive(Serialize, Debug)]
 struct SnippetTypeContext<'a> {
     name: &'a str,
-    alias: Option<String>,
+    aliases: Vec<AliasContext>,
 }
 
-#[instrument(level = "debug", , skip(writer))]
+#[derive(Serialize, Debug)]
+struct AliasContext {
+    name: String,
+    action: AliasAction,
+    /// `rsnip` arguments of the action
+    args: &'static str,
+    description: String,
+}
+
+/// A subcommand of the command line definition with its long options
+#[derive(Serialize, Debug)]
+struct CommandContext {
+    name: String,
+    options: Vec<String>,
+}
+
+/// Shells `--generate` knows about
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ShellKind {
+    Bash,
+    Elvish,
+    Fish,
+    Nushell,
+    Powershell,
+    Zsh,
+}
+
+/// Characters that would need quoting in an alias or function name
+const INVALID_ALIAS_CHARS: &[char] = &['\'', '"', '`', '\\', '$', '=', '/', ';', '|', '&', '<', '>', '(', ')', '#'];
+
+/// Builtins and keywords of POSIX shells, shared by bash and zsh
+const SH_RESERVED: &[&str] = &[
+    ".", ":", "[", "[[", "]]", "{", "}", "!", "alias", "bg", "break", "builtin", "case", "cd",
+    "command", "continue", "declare", "do", "done", "echo", "elif", "else", "enable", "esac",
+    "eval", "exec", "exit", "export", "false", "fc", "fg", "fi", "for", "function", "getopts",
+    "hash", "if", "in", "jobs", "kill", "let", "local", "printf", "pwd", "read", "readonly",
+    "return", "select", "set", "shift", "source", "test", "then", "time", "times", "trap", "true",
+    "type", "typeset", "ulimit", "umask", "unalias", "unset", "until", "wait", "while",
+];
+
+const BASH_RESERVED: &[&str] = &[
+    "bind", "caller", "compgen", "complete", "compopt", "coproc", "dirs", "disown", "help",
+    "history", "logout", "mapfile", "popd", "pushd", "readarray", "shopt", "suspend",
+];
+
+const ZSH_RESERVED: &[&str] = &[
+    "autoload", "bindkey", "compdef", "dirs", "disown", "emulate", "foreach", "functions",
+    "history", "noglob", "popd", "print", "pushd", "repeat", "setopt", "unsetopt", "whence",
+    "where", "which", "zle", "zmodload",
+];
+
+const FISH_RESERVED: &[&str] = &[
+    "and", "begin", "bind", "block", "break", "builtin", "case", "cd", "command", "commandline",
+    "complete", "contains", "continue", "count", "echo", "else", "emit", "end", "eval", "exec",
+    "exit", "false", "for", "function", "functions", "if", "math", "not", "or", "printf", "read",
+    "return", "set", "set_color", "source", "status", "string", "switch", "test", "true", "while",
+];
+
+const NUSHELL_RESERVED: &[&str] = &[
+    "alias", "break", "cd", "commandline", "complete", "const", "continue", "def", "do", "each",
+    "echo", "else", "export", "for", "get", "hide", "if", "ignore", "let", "loop", "ls", "match",
+    "module", "mut", "open", "overlay", "print", "return", "save", "select", "source", "try",
+    "use", "where", "while",
+];
+
+impl ShellKind {
+    /// Whether `name` is a builtin or keyword an alias would shadow
+    fn is_reserved(self, name: &str) -> bool {
+        let words: &[&[&str]] = match self {
+            ShellKind::Bash => &[SH_RESERVED, BASH_RESERVED],
+            ShellKind::Zsh => &[SH_RESERVED, ZSH_RESERVED],
+            ShellKind::Fish => &[FISH_RESERVED],
+            ShellKind::Nushell => &[NUSHELL_RESERVED],
+            ShellKind::Elvish | ShellKind::Powershell => &[],
+        };
+        name == "rsnip" || words.iter().any(|words| words.contains(&name))
+    }
+}
+
+#[instrument(level = "debug", , skip(cmd, writer))]
 pub fn generate_completion_script(
-    shell: Shell,
-    mut writer: impl Write,
+    shell: ShellKind,
+    cmd: &Command,
+    writer: impl Write,
     config: &Settings,
 ) -> Result<()> {
     match shell {
-        Shell::Bash => {
-            let mut env = Environment::new();
-            env.set_debug(true); // Enable debug mode
-
-            // Load the template
-            env.add_template(
-                "bash_completion",
-                include_str!("../../rsnip.alias.bash.template"),
-            )?;
-
-            // Get template
-            let tmpl = env.get_template("bash_completion")?;
-            debug!("Loaded template: {:?}", tmpl);
-
-            // Create context with snippet types and their aliases
-            let snippet_types: Vec<_> = config.snippet_types
-                .iter()
-                .map(|(name, cfg)| {
-                    let alias = match cfg {
-                        SnippetTypeConfig::Concrete { alias, .. } => alias.clone(),
-                        SnippetTypeConfig::Combined { alias, .. } => alias.clone(),
-                    };
-                    SnippetTypeContext {
-                        name,
-                        alias,
-                    }
+        ShellKind::Bash => render_script(
+            shell,
+            include_str!("../../rsnip.alias.bash.template"),
+            cmd,
+            "",
+            writer,
+            config,
+        ),
+        ShellKind::Zsh => render_script(
+            shell,
+            include_str!("../../rsnip.alias.zsh.template"),
+            cmd,
+            &clap_script(clap_complete::Shell::Zsh, cmd)?,
+            writer,
+            config,
+        ),
+        ShellKind::Fish => render_script(
+            shell,
+            include_str!("../../rsnip.alias.fish.template"),
+            cmd,
+            &clap_script(clap_complete::Shell::Fish, cmd)?,
+            writer,
+            config,
+        ),
+        ShellKind::Nushell => render_script(
+            shell,
+            include_str!("../../rsnip.alias.nu.template"),
+            cmd,
+            "",
+            writer,
+            config,
+        ),
+        ShellKind::Elvish => render_script(
+            shell,
+            include_str!("../../rsnip.alias.elvish.template"),
+            cmd,
+            &clap_script(clap_complete::Shell::Elvish, cmd)?,
+            writer,
+            config,
+        ),
+        ShellKind::Powershell => render_script(
+            shell,
+            include_str!("../../rsnip.alias.powershell.template"),
+            cmd,
+            &powershell_completions(&clap_script(clap_complete::Shell::PowerShell, cmd)?)?,
+            writer,
+            config,
+        ),
+    }
+}
+
+/// tmux key bindings opening the snippet finder in a popup
+#[instrument(level = "debug", skip(writer))]
+pub fn generate_tmux_bindings(mut writer: impl Write, config: &Settings) -> Result<()> {
+    let mut snippet_types: Vec<&str> = config.snippet_types.keys().map(String::as_str).collect();
+    snippet_types.sort();
+
+    let mut env = Environment::new();
+    env.add_template("tmux", include_str!("../../rsnip.alias.tmux.template"))?;
+    let rendered = env
+        .get_template("tmux")?
+        .render(minijinja::context! { snippet_types => snippet_types })?;
+    writer.write_all(rendered.as_bytes())?;
+    Ok(())
+}
+
+/// Clap's completion script for all subcommands and flags. Snippet types and names are
+/// completed by the shell templates, which ask rsnip at completion time.
+fn clap_script(shell: clap_complete::Shell, cmd: &Command) -> Result<String> {
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut cmd.clone(), "rsnip", &mut script);
+    Ok(String::from_utf8(script)?)
+}
+
+/// Clap's PowerShell completer kept in a variable, so the registered completer can
+/// fall back to it for everything but option values
+fn powershell_completions(script: &str) -> Result<String> {
+    const REGISTRATION: &str =
+        "Register-ArgumentCompleter -Native -CommandName 'rsnip' -ScriptBlock {";
+    if !script.contains(REGISTRATION) {
+        return Err(anyhow::anyhow!(
+            "Unexpected PowerShell completion script from clap"
+        ));
+    }
+    Ok(script.replacen(REGISTRATION, "$global:RsnipClapCompleter = {", 1))
+}
+
+/// Subcommands with their long options, including the global ones, for shells clap
+/// generates no completions for
+fn command_options(cmd: &Command) -> Vec<CommandContext> {
+    let global_options: Vec<String> = cmd
+        .get_arguments()
+        .filter(|arg| arg.is_global_set())
+        .filter_map(|arg| arg.get_long())
+        .map(|long| format!("--{}", long))
+        .collect();
+    cmd.get_subcommands()
+        .filter(|sub| !sub.is_hide_set())
+        .map(|sub| CommandContext {
+            name: sub.get_name().to_string(),
+            options: sub
+                .get_arguments()
+                .filter(|arg| !arg.is_hide_set())
+                .filter_map(|arg| arg.get_long())
+                .map(|long| format!("--{}", long))
+                .chain(global_options.iter().cloned())
+                .collect(),
+        })
+        .collect()
+}
+
+/// Render a shell integration template with the configured snippet types and aliases
+fn render_script(
+    shell: ShellKind,
+    template: &str,
+    cmd: &Command,
+    clap_completions: &str,
+    mut writer: impl Write,
+    config: &Settings,
+) -> Result<()> {
+    let mut env = Environment::new();
+    env.set_debug(true); // Enable debug mode
+
+    // Load the template
+    env.add_template("completion", template)?;
+
+    // Get template
+    let tmpl = env.get_template("completion")?;
+    debug!("Loaded template: {:?}", tmpl);
+
+    // Create context with snippet types and their aliases, sorted for stable output
+    let mut snippet_types: Vec<_> = config.snippet_types
+        .iter()
+        .map(|(name, cfg)| {
+            let aliases = cfg
+                .shell_aliases()
+                .into_iter()
+                .map(|(action, alias)| AliasContext {
+                    name: alias,
+                    action,
+                    args: action.args(),
+                    description: format!("{} a {} snippet", action.verb(), name),
                 })
                 .collect();
-            debug!("Found snippet types with aliases: {:?}", snippet_types);
+            SnippetTypeContext {
+                name,
+                aliases,
+            }
+        })
+        .collect();
+    snippet_types.sort_by(|a, b| a.name.cmp(b.name));
+    debug!("Found snippet types with aliases: {:?}", snippet_types);
+    validate_aliases(shell, &snippet_types)?;
+
+    let context = minijinja::context! {
+        snippet_types => snippet_types,
+        clap_completions => clap_completions,
+        commands => command_options(cmd),
+        shell_functions => config.shell_functions,
+    };
+
+    // Render and write
+    let rendered = tmpl.render(context)?;
+    writer.write_all(rendered.as_bytes())?;
+
+    Ok(())
+}
+
+/// Check that alias names need no quoting, are unique and do not shadow shell builtins
+fn validate_aliases(shell: ShellKind, snippet_types: &[SnippetTypeContext]) -> Result<()> {
+    let mut seen: HashMap<&str, (&str, AliasAction)> = HashMap::new();
+    let mut problems = Vec::new();
 
-            let context = minijinja::context! {
-                snippet_types => snippet_types,
-            };
+    for snippet_type in snippet_types {
+        for alias in &snippet_type.aliases {
+            if alias.name.contains(|c: char| c.is_whitespace() || INVALID_ALIAS_CHARS.contains(&c)) {
+                problems.push(format!(
+                    "{} alias '{}' of '{}' contains characters not allowed in a command name",
+                    alias.action, alias.name, snippet_type.name
+                ));
+            } else if shell.is_reserved(&alias.name) {
+                problems.push(format!(
+                    "{} alias '{}' of '{}' shadows a shell builtin or keyword",
+                    alias.action, alias.name, snippet_type.name
+                ));
+            }
+            if let Some((other_type, other_action)) =
+                seen.insert(&alias.name, (snippet_type.name, alias.action))
+            {
+                problems.push(format!(
+                    "alias '{}' is used for both {} of '{}' and {} of '{}'",
+                    alias.name, other_action, other_type, alias.action, snippet_type.name
+                ));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("Invalid shell aliases:\n  {}", problems.join("\n  ")))
+    }
+}
 
-            // Render and write
-            let rendered = tmpl.render(context)?;
-            writer.write_all(rendered.as_bytes())?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::args::Cli;
+    use crate::config::{AliasConfig, SnippetTypeConfig};
+    use clap::CommandFactory;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
 
-            Ok(())
+    fn create_settings() -> Settings {
+        let mut snippet_types = HashMap::new();
+        snippet_types.insert(
+            "shell".to_string(),
+            SnippetTypeConfig::Concrete {
+                source_file: PathBuf::from("shell.txt"),
+                description: None,
+                alias: Some(",".to_string()),
+                format: "default".to_string(),
+                vars: HashMap::new(),
+                output: None,
+                aliases: AliasConfig::default(),
+            },
+        );
+        Settings {
+            snippet_types,
+            ..Default::default()
         }
-        _ => Err(anyhow::anyhow!(
-            "Only Bash completion is currently supported"
-        )),
+    }
+
+    #[test]
+    fn given_zsh_when_generating_then_defines_aliases_completion_and_widget() {
+        let mut script = Vec::new();
+
+        generate_completion_script(
+            ShellKind::Zsh,
+            &Cli::command(),
+            &mut script,
+            &create_settings(),
+        )
+        .unwrap();
+
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains(r#"alias ','="rsnip copy --ctype shell --input""#));
+        assert!(script.contains(r#"alias 'e,'="rsnip edit --ctype shell --input""#));
+        assert!(script.contains("compdef _rsnip ',' 'e,'"));
+        assert!(script.contains("functions[_rsnip_clap]=$functions[_rsnip]"));
+        assert!(script.contains(r#"compadd -- ${=$(rsnip types --list 2>/dev/null)}"#));
+        assert!(script.contains("zle -N rsnip-widget _rsnip_widget"));
+        assert!(script.contains("CURSOR=$(( start + cursor ))"));
+    }
+
+    #[test]
+    fn given_fish_when_generating_then_defines_functions_completions_and_binding() {
+        let mut script = Vec::new();
+
+        generate_completion_script(
+            ShellKind::Fish,
+            &Cli::command(),
+            &mut script,
+            &create_settings(),
+        )
+        .unwrap();
+
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("function ',' --wraps 'rsnip copy'"));
+        assert!(script.contains("    rsnip edit --ctype shell --input $argv[1]"));
+        assert!(script.contains("complete -c rsnip -l ctype -f -a '(__rsnip_snippet_types)'"));
+        assert!(script.contains("complete -c rsnip -l input -f -a '(__rsnip_snippet_names)'"));
+        assert!(script.contains("case ',' 'e,'\n                set ctype shell"));
+        assert!(script.contains("bind \\cxs __rsnip_insert"));
+    }
+
+    #[test]
+    fn given_nushell_when_generating_then_defines_commands_completer_and_keybinding() {
+        let mut script = Vec::new();
+
+        generate_completion_script(
+            ShellKind::Nushell,
+            &Cli::command(),
+            &mut script,
+            &create_settings(),
+        )
+        .unwrap();
+
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains(
+            r#"def "," [input: string@"nu-complete rsnip"] { rsnip copy --ctype shell --input $input }"#
+        ));
+        assert!(script.contains(r#""," | "e," => "shell","#));
+        assert!(script.contains("$env.config.completions.external.completer = {|spans|"));
+        assert!(script.contains(r#"cmd: "rsnip-insert""#));
+    }
+
+    #[test]
+    fn given_nushell_when_generating_then_completes_every_subcommand_and_option() {
+        let cmd = Cli::command();
+        let mut script = Vec::new();
+
+        generate_completion_script(ShellKind::Nushell, &cmd, &mut script, &create_settings())
+            .unwrap();
+
+        let script = String::from_utf8(script).unwrap();
+        for sub in cmd.get_subcommands() {
+            assert!(
+                script.contains(&format!("\"{}\": [", sub.get_name())),
+                "missing subcommand {}",
+                sub.get_name()
+            );
+        }
+        assert!(script.contains(r#""copy": ["--ctype" "--input" "--to" "--pane""#));
+        assert!(script.contains(r#""--format""#));
+        assert!(script.contains(r#""--dry-run""#));
+        assert!(script.contains(r#""--seed""#));
+    }
+
+    #[test]
+    fn given_bash_when_generating_then_defines_aliases() {
+        let mut script = Vec::new();
+
+        generate_completion_script(
+            ShellKind::Bash,
+            &Cli::command(),
+            &mut script,
+            &create_settings(),
+        )
+        .unwrap();
+
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains(r#"alias ,="rsnip copy --ctype shell --input""#));
+        assert!(script.contains(r#"complete -F _rsnip_complete "e,""#));
+        assert!(script.contains("READLINE_POINT=$(( ${#before} + cursor ))"));
+    }
+
+    #[test]
+    fn given_elvish_when_generating_then_wraps_clap_completer_with_values() {
+        let mut script = Vec::new();
+
+        generate_completion_script(
+            ShellKind::Elvish,
+            &Cli::command(),
+            &mut script,
+            &create_settings(),
+        )
+        .unwrap();
+
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("set edit:completion:arg-completer[rsnip] = {|@words|"));
+        assert!(script.contains("cand scaffold"));
+        assert!(script.contains("str:fields (rsnip types --list 2>/dev/null)"));
+        assert!(script.contains("rsnip list --names-only --ctype $ctype"));
+    }
+
+    #[test]
+    fn given_powershell_when_generating_then_falls_back_to_clap_completer() {
+        let mut script = Vec::new();
+
+        generate_completion_script(
+            ShellKind::Powershell,
+            &Cli::command(),
+            &mut script,
+            &create_settings(),
+        )
+        .unwrap();
+
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.starts_with("\nusing namespace") || script.starts_with("using namespace"));
+        assert!(script.contains("$global:RsnipClapCompleter = {"));
+        assert_eq!(script.matches("Register-ArgumentCompleter").count(), 1);
+        assert!(script.contains("rsnip types --list 2>$null"));
+    }
+
+    fn create_settings_with_aliases(aliases: AliasConfig, shell_functions: bool) -> Settings {
+        let mut settings = create_settings();
+        settings.shell_functions = shell_functions;
+        if let Some(SnippetTypeConfig::Concrete { aliases: type_aliases, .. }) =
+            settings.snippet_types.get_mut("shell")
+        {
+            *type_aliases = aliases;
+        }
+        settings
+    }
+
+    #[test]
+    fn given_shell_functions_when_generating_bash_then_defines_functions_per_action() {
+        let settings = create_settings_with_aliases(
+            AliasConfig {
+                edit_alias: Some(String::new()),
+                exec_alias: Some("x,".to_string()),
+                render_alias: Some("p,".to_string()),
+                ..Default::default()
+            },
+            true,
+        );
+        let mut script = Vec::new();
+
+        generate_completion_script(ShellKind::Bash, &Cli::command(), &mut script, &settings).unwrap();
+
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains(r#",() { rsnip copy --ctype shell --input "$1"; }"#));
+        assert!(script.contains(
+            r#"x,() { eval "$(rsnip expand --format text --ctype shell --input "$1")"; }"#
+        ));
+        assert!(script.contains(r#"p,() { rsnip expand --format text --ctype shell --input "$1"; }"#));
+        assert!(!script.contains("unalias e,"));
+        assert!(!script.contains("alias ,="));
+    }
+
+    #[test]
+    fn given_colliding_or_builtin_aliases_when_generating_then_reports_all_problems() {
+        let settings = create_settings_with_aliases(
+            AliasConfig {
+                edit_alias: Some(",".to_string()),
+                exec_alias: Some("source".to_string()),
+                render_alias: Some("my alias".to_string()),
+                ..Default::default()
+            },
+            false,
+        );
+
+        let err = generate_completion_script(ShellKind::Zsh, &Cli::command(), Vec::new(), &settings)
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("alias ',' is used for both copy of 'shell' and edit of 'shell'"));
+        assert!(err.contains("exec alias 'source' of 'shell' shadows a shell builtin or keyword"));
+        assert!(err.contains("render alias 'my alias' of 'shell' contains characters"));
+    }
+
+    #[test]
+    fn given_shell_specific_builtin_when_checking_then_only_that_shell_rejects_it() {
+        assert!(ShellKind::Fish.is_reserved("string"));
+        assert!(!ShellKind::Bash.is_reserved("string"));
+        assert!(ShellKind::Zsh.is_reserved("bindkey"));
+        assert!(ShellKind::Nushell.is_reserved("rsnip"));
+    }
+
+    #[test]
+    fn given_tmux_when_generating_then_binds_popup_sending_to_pane() {
+        let mut script = Vec::new();
+
+        generate_tmux_bindings(&mut script, &create_settings()).unwrap();
+
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("bind-key S display-popup -E"));
+        assert!(script.contains(r#"--input "$name" --to tmux'"#));
+        assert!(script.contains("# snippet types: shell\n"));
     }
 }
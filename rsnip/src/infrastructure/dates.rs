@@ -0,0 +1,358 @@
+// infrastructure/dates.rs
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, TimeZone,
+    Utc, Weekday,
+};
+use chrono_tz::Tz;
+use minijinja::{Environment, Error, ErrorKind};
+use std::str::FromStr;
+
+/// Timezone used for `current_date` and for interpreting dates without offset
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TemplateTimezone {
+    #[default]
+    Local,
+    Named(Tz),
+}
+
+impl FromStr for TemplateTimezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("local") {
+            return Ok(Self::Local);
+        }
+        s.parse::<Tz>()
+            .map(Self::Named)
+            .map_err(|_| format!("Unknown timezone: {}", s))
+    }
+}
+
+impl TemplateTimezone {
+    pub fn convert(&self, date: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            Self::Local => date.with_timezone(&Local).fixed_offset(),
+            Self::Named(tz) => date.with_timezone(tz).fixed_offset(),
+        }
+    }
+
+    /// Interpret a wall-clock time in this timezone, picking the earlier time on DST overlaps
+    pub fn localize(&self, date: NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        match self {
+            Self::Local => Local
+                .from_local_datetime(&date)
+                .earliest()
+                .map(|d| d.fixed_offset()),
+            Self::Named(tz) => tz
+                .from_local_datetime(&date)
+                .earliest()
+                .map(|d| d.fixed_offset()),
+        }
+    }
+}
+
+/// Source of the current time for templates
+#[derive(Clone, Debug, Default)]
+pub struct Clock {
+    timezone: TemplateTimezone,
+    fixed_now: Option<DateTime<FixedOffset>>,
+}
+
+impl Clock {
+    pub fn new(timezone: TemplateTimezone) -> Self {
+        Self {
+            timezone,
+            fixed_now: None,
+        }
+    }
+
+    /// Freeze the current time. The offset of `now` is kept as given so output
+    /// does not depend on the timezone of the machine rendering the template.
+    pub fn with_fixed_now(mut self, now: DateTime<FixedOffset>) -> Self {
+        self.fixed_now = Some(now);
+        self
+    }
+
+    pub fn now(&self) -> DateTime<FixedOffset> {
+        self.fixed_now
+            .unwrap_or_else(|| self.timezone.convert(Utc::now()))
+    }
+
+    /// Interpret a wall-clock time, using the offset of a frozen time if set
+    fn localize(&self, date: NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        match self.fixed_now {
+            Some(now) => now.offset().from_local_datetime(&date).single(),
+            None => self.timezone.localize(date),
+        }
+    }
+
+    /// Parse RFC3339, `YYYY-MM-DD[ HH:MM[:SS]]` or a relative expression like "next monday"
+    pub fn parse(&self, input: &str) -> Result<DateTime<FixedOffset>, Error> {
+        let input = input.trim();
+        if let Ok(date) = DateTime::parse_from_rfc3339(input) {
+            return Ok(date);
+        }
+
+        let naive = NaiveDate::parse_from_str(input, "%Y-%m-%d")
+            .ok()
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .or_else(|| NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M:%S").ok())
+            .or_else(|| NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M").ok());
+        if let Some(naive) = naive {
+            return self
+                .localize(naive)
+                .ok_or_else(|| invalid(format!("Date does not exist in timezone: {}", input)));
+        }
+
+        parse_relative(self, input).ok_or_else(|| invalid(format!("Invalid date: {}", input)))
+    }
+}
+
+/// Register date filters and functions, interpreting dates relative to `clock`
+pub fn register(env: &mut Environment<'static>, clock: Clock) {
+    let c = clock.clone();
+    env.add_filter("strftime", move |value: &str, format: Option<&str>| {
+        let date = c.parse(value)?;
+        Ok::<_, Error>(date.format(format.unwrap_or("%Y-%m-%d")).to_string())
+    });
+
+    let c = clock.clone();
+    env.add_filter("add_days", move |value: &str, days: Option<i64>| {
+        shift(&c, value, Duration::days(days.unwrap_or(0)))
+    });
+    let c = clock.clone();
+    env.add_filter("subtract_days", move |value: &str, days: Option<i64>| {
+        shift(&c, value, -Duration::days(days.unwrap_or(0)))
+    });
+    let c = clock.clone();
+    env.add_filter("add_hours", move |value: &str, hours: Option<i64>| {
+        shift(&c, value, Duration::hours(hours.unwrap_or(0)))
+    });
+    let c = clock.clone();
+    env.add_filter("add_weeks", move |value: &str, weeks: Option<i64>| {
+        shift(&c, value, Duration::weeks(weeks.unwrap_or(0)))
+    });
+    let c = clock.clone();
+    env.add_filter("add_months", move |value: &str, months: Option<i64>| {
+        add_months(&c, value, months.unwrap_or(0))
+    });
+
+    let c = clock.clone();
+    env.add_filter("to_tz", move |value: &str, timezone: &str| {
+        let date = c.parse(value)?;
+        let timezone = TemplateTimezone::from_str(timezone).map_err(invalid)?;
+        Ok::<_, Error>(timezone.convert(date.to_utc()).to_rfc3339())
+    });
+
+    let c = clock.clone();
+    env.add_filter("next_weekday", move |value: &str, weekday: &str| {
+        let date = c.parse(value)?;
+        let weekday = parse_weekday(weekday)?;
+        Ok::<_, Error>(
+            (date + Duration::days(days_until(date.weekday(), weekday, false))).to_rfc3339(),
+        )
+    });
+
+    let c = clock.clone();
+    env.add_filter("iso_week", move |value: &str| {
+        Ok::<_, Error>(c.parse(value)?.iso_week().week())
+    });
+
+    env.add_function("parse_date", move |expression: &str| {
+        Ok::<_, Error>(clock.parse(expression)?.to_rfc3339())
+    });
+}
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::new(ErrorKind::InvalidOperation, msg.into())
+}
+
+fn shift(clock: &Clock, value: &str, duration: Duration) -> Result<String, Error> {
+    let date = clock.parse(value)?;
+    date.checked_add_signed(duration)
+        .map(|d| d.to_rfc3339())
+        .ok_or_else(|| invalid("Date out of range"))
+}
+
+fn add_months(clock: &Clock, value: &str, months: i64) -> Result<String, Error> {
+    let date = clock.parse(value)?;
+    shift_months(date, months)
+        .map(|d| d.to_rfc3339())
+        .ok_or_else(|| invalid("Date out of range"))
+}
+
+/// Calendar month arithmetic, clamping to the last day of shorter months
+fn shift_months(date: DateTime<FixedOffset>, months: i64) -> Option<DateTime<FixedOffset>> {
+    let delta = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+    if months >= 0 {
+        date.checked_add_months(delta)
+    } else {
+        date.checked_sub_months(delta)
+    }
+}
+
+fn parse_weekday(name: &str) -> Result<Weekday, Error> {
+    name.trim()
+        .parse::<Weekday>()
+        .map_err(|_| invalid(format!("Invalid weekday: {}", name)))
+}
+
+/// Days from `from` to the next `to`; a same-day match counts as zero only if `inclusive`
+fn days_until(from: Weekday, to: Weekday, inclusive: bool) -> i64 {
+    let days = (7 + to.num_days_from_monday() as i64 - from.num_days_from_monday() as i64) % 7;
+    if days == 0 && !inclusive {
+        7
+    } else {
+        days
+    }
+}
+
+/// Parse expressions like "tomorrow", "next friday", "last month", "in 3 days", "2 weeks ago".
+/// Day-based expressions resolve to midnight, unit offsets keep the time of day.
+fn parse_relative(clock: &Clock, input: &str) -> Option<DateTime<FixedOffset>> {
+    let input = input.to_lowercase();
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let now = clock.now();
+    let today = now.date_naive();
+    let midnight = |date: NaiveDate| clock.localize(date.and_hms_opt(0, 0, 0)?);
+
+    match words.as_slice() {
+        ["now"] => Some(now),
+        ["today"] => midnight(today),
+        ["tomorrow"] => midnight(today.succ_opt()?),
+        ["yesterday"] => midnight(today.pred_opt()?),
+        [direction @ ("next" | "last" | "this"), unit] => {
+            if let Ok(weekday) = unit.parse::<Weekday>() {
+                let offset = match *direction {
+                    "next" => days_until(today.weekday(), weekday, false),
+                    "this" => days_until(today.weekday(), weekday, true),
+                    _ => -days_until(weekday, today.weekday(), false),
+                };
+                return midnight(today + Duration::days(offset));
+            }
+            let amount = match *direction {
+                "next" => 1,
+                "last" => -1,
+                _ => 0,
+            };
+            offset_by_unit(now, amount, unit)
+        }
+        [weekday] => {
+            let weekday = weekday.parse::<Weekday>().ok()?;
+            midnight(today + Duration::days(days_until(today.weekday(), weekday, true)))
+        }
+        ["in", amount, unit] => offset_by_unit(now, amount.parse().ok()?, unit),
+        [amount, unit, "ago"] => offset_by_unit(now, -amount.parse::<i64>().ok()?, unit),
+        [amount, unit] if amount.starts_with(['+', '-']) => {
+            offset_by_unit(now, amount.parse().ok()?, unit)
+        }
+        _ => None,
+    }
+}
+
+fn offset_by_unit(
+    now: DateTime<FixedOffset>,
+    amount: i64,
+    unit: &str,
+) -> Option<DateTime<FixedOffset>> {
+    let duration = match unit.trim_end_matches('s') {
+        "minute" | "min" => Duration::try_minutes(amount)?,
+        "hour" => Duration::try_hours(amount)?,
+        "day" => Duration::try_days(amount)?,
+        "week" => Duration::try_weeks(amount)?,
+        "month" => return shift_months(now, amount),
+        "year" => return shift_months(now, amount.checked_mul(12)?),
+        _ => return None,
+    };
+    now.checked_add_signed(duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixed_now() -> DateTime<FixedOffset> {
+        // Wednesday
+        DateTime::parse_from_rfc3339("2025-01-15T14:30:00+00:00").unwrap()
+    }
+
+    fn fixed_clock() -> Clock {
+        Clock::new(TemplateTimezone::Named(Tz::Asia__Tokyo)).with_fixed_now(fixed_now())
+    }
+
+    fn parse(input: &str) -> String {
+        parse_relative(&fixed_clock(), input)
+            .unwrap_or_else(|| panic!("Failed to parse '{}'", input))
+            .to_rfc3339()
+    }
+
+    #[test]
+    fn given_day_expressions_when_parsing_then_returns_midnight() {
+        assert_eq!(parse("today"), "2025-01-15T00:00:00+00:00");
+        assert_eq!(parse("Tomorrow"), "2025-01-16T00:00:00+00:00");
+        assert_eq!(parse("yesterday"), "2025-01-14T00:00:00+00:00");
+    }
+
+    #[test]
+    fn given_weekday_expressions_when_parsing_then_returns_matching_day() {
+        assert_eq!(parse("next monday"), "2025-01-20T00:00:00+00:00");
+        assert_eq!(parse("next wednesday"), "2025-01-22T00:00:00+00:00");
+        assert_eq!(parse("this wednesday"), "2025-01-15T00:00:00+00:00");
+        assert_eq!(parse("friday"), "2025-01-17T00:00:00+00:00");
+        assert_eq!(parse("last fri"), "2025-01-10T00:00:00+00:00");
+        assert_eq!(parse("last wednesday"), "2025-01-08T00:00:00+00:00");
+    }
+
+    #[test]
+    fn given_unit_offsets_when_parsing_then_keeps_time_of_day() {
+        assert_eq!(parse("in 3 days"), "2025-01-18T14:30:00+00:00");
+        assert_eq!(parse("2 weeks ago"), "2025-01-01T14:30:00+00:00");
+        assert_eq!(parse("+2 hours"), "2025-01-15T16:30:00+00:00");
+        assert_eq!(parse("next month"), "2025-02-15T14:30:00+00:00");
+        assert_eq!(parse("last year"), "2024-01-15T14:30:00+00:00");
+    }
+
+    #[test]
+    fn given_unknown_expression_when_parsing_then_returns_none() {
+        assert!(parse_relative(&fixed_clock(), "someday").is_none());
+        assert!(parse_relative(&fixed_clock(), "in x days").is_none());
+    }
+
+    #[test]
+    fn given_fixed_now_when_parsing_dates_then_uses_its_offset() {
+        let clock = Clock::new(TemplateTimezone::Named(Tz::Asia__Tokyo))
+            .with_fixed_now(DateTime::parse_from_rfc3339("2025-01-15T09:00:00-05:00").unwrap());
+
+        assert_eq!(clock.now().to_rfc3339(), "2025-01-15T09:00:00-05:00");
+        assert_eq!(
+            clock.parse("2025-02-01").unwrap().to_rfc3339(),
+            "2025-02-01T00:00:00-05:00"
+        );
+    }
+
+    #[test]
+    fn given_month_end_when_shifting_months_then_clamps_day() {
+        let date = DateTime::parse_from_rfc3339("2024-01-31T10:00:00+01:00").unwrap();
+        assert_eq!(
+            shift_months(date, 1).unwrap().to_rfc3339(),
+            "2024-02-29T10:00:00+01:00"
+        );
+        assert_eq!(
+            shift_months(date, -2).unwrap().to_rfc3339(),
+            "2023-11-30T10:00:00+01:00"
+        );
+    }
+
+    #[test]
+    fn given_timezone_names_when_parsing_then_accepts_local_and_iana() {
+        assert_eq!(
+            "local".parse::<TemplateTimezone>().unwrap(),
+            TemplateTimezone::Local
+        );
+        assert_eq!(
+            "Europe/Berlin".parse::<TemplateTimezone>().unwrap(),
+            TemplateTimezone::Named(Tz::Europe__Berlin)
+        );
+        assert!("Mars/Olympus".parse::<TemplateTimezone>().is_err());
+    }
+}
@@ -0,0 +1,141 @@
+// infrastructure/env_vars.rs
+use minijinja::{Environment, Error, ErrorKind, Value};
+use tracing::debug;
+
+/// Name patterns of variables that commonly hold secrets, hidden unless allowlisted
+pub const DEFAULT_ENV_DENYLIST: &[&str] = &[
+    "*SECRET*",
+    "*TOKEN*",
+    "*PASSWORD*",
+    "*PASSWD*",
+    "*CREDENTIAL*",
+    "*API_KEY*",
+    "*APIKEY*",
+    "*PRIVATE_KEY*",
+    "*ACCESS_KEY*",
+    "*SESSION_KEY*",
+];
+
+/// Decides which environment variables templates may read.
+/// Patterns are case-insensitive and support `*` as wildcard.
+#[derive(Clone, Debug)]
+pub struct EnvPolicy {
+    allowlist: Vec<String>,
+    denylist: Vec<String>,
+}
+
+impl Default for EnvPolicy {
+    fn default() -> Self {
+        Self::new(
+            Vec::new(),
+            DEFAULT_ENV_DENYLIST.iter().map(|p| p.to_string()).collect(),
+        )
+    }
+}
+
+impl EnvPolicy {
+    pub fn new(allowlist: Vec<String>, denylist: Vec<String>) -> Self {
+        Self {
+            allowlist,
+            denylist,
+        }
+    }
+
+    /// An allowlisted name is always visible. With a non-empty allowlist nothing else is,
+    /// otherwise everything not matching the denylist.
+    pub fn is_allowed(&self, name: &str) -> bool {
+        if self.allowlist.iter().any(|p| matches_pattern(p, name)) {
+            return true;
+        }
+        self.allowlist.is_empty() && !self.denylist.iter().any(|p| matches_pattern(p, name))
+    }
+
+    /// Visible variables of the process environment
+    pub fn visible_vars(&self) -> impl Iterator<Item = (String, String)> + '_ {
+        std::env::vars().filter(|(key, _)| self.is_allowed(key))
+    }
+}
+
+/// Register `env('NAME', default)`, reading the environment at call time
+pub fn register(env: &mut Environment<'static>, policy: EnvPolicy) {
+    env.add_function("env", move |name: &str, default: Option<Value>| {
+        if !policy.is_allowed(name) {
+            debug!("Blocked template access to environment variable {}", name);
+            return Err(Error::new(
+                ErrorKind::InvalidOperation,
+                format!(
+                    "Environment variable {} is not exposed to templates, add it to env_allowlist",
+                    name
+                ),
+            ));
+        }
+        Ok(match std::env::var(name) {
+            Ok(value) => Value::from(value),
+            Err(_) => default.unwrap_or(Value::UNDEFINED),
+        })
+    });
+}
+
+/// Glob match with `*` wildcards, ignoring ASCII case
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_ascii_uppercase();
+    let name = name.to_ascii_uppercase();
+
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if name.len() < first.len() + last.len() || !name.starts_with(first) || !name.ends_with(last)
+    {
+        return false;
+    }
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_patterns_when_matching_then_handles_wildcards_and_case() {
+        assert!(matches_pattern("*TOKEN*", "github_token"));
+        assert!(matches_pattern("AWS_*", "AWS_PROFILE"));
+        assert!(matches_pattern("*_KEY", "SSH_KEY"));
+        assert!(matches_pattern("HOME", "home"));
+        assert!(!matches_pattern("HOME", "HOMEBREW_PREFIX"));
+        assert!(!matches_pattern("A*B*A", "ABA_"));
+        assert!(!matches_pattern("AB*BA", "ABA"));
+    }
+
+    #[test]
+    fn given_default_policy_when_checking_then_hides_secrets() {
+        let policy = EnvPolicy::default();
+
+        assert!(policy.is_allowed("HOME"));
+        assert!(policy.is_allowed("AWS_PROFILE"));
+        assert!(!policy.is_allowed("AWS_SECRET_ACCESS_KEY"));
+        assert!(!policy.is_allowed("GITHUB_TOKEN"));
+        assert!(!policy.is_allowed("DB_PASSWORD"));
+    }
+
+    #[test]
+    fn given_allowlist_when_checking_then_only_allowlisted_are_visible() {
+        let policy = EnvPolicy::new(
+            vec!["HOME".to_string(), "VAULT_TOKEN".to_string()],
+            DEFAULT_ENV_DENYLIST.iter().map(|p| p.to_string()).collect(),
+        );
+
+        assert!(policy.is_allowed("HOME"));
+        assert!(policy.is_allowed("VAULT_TOKEN"));
+        assert!(!policy.is_allowed("USER"));
+    }
+}
@@ -0,0 +1,138 @@
+// infrastructure/filters.rs
+use crate::domain::output::{dedent, shell_quote};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use heck::{ToKebabCase, ToLowerCamelCase, ToSnakeCase, ToUpperCamelCase};
+use md5::Md5;
+use minijinja::{Environment, Error, ErrorKind};
+use rand::distr::Alphanumeric;
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, Mutex};
+
+/// Shared random source for template functions
+pub type SharedRng = Arc<Mutex<StdRng>>;
+
+/// Register the standard filter and function library on the environment.
+/// Random functions draw from `rng` so callers can control seeding.
+pub fn register(env: &mut Environment<'static>, rng: SharedRng) {
+    // Case conversion
+    env.add_filter("snake_case", snake_case);
+    env.add_filter("camel_case", camel_case);
+    env.add_filter("kebab_case", kebab_case);
+    env.add_filter("pascal_case", pascal_case);
+    env.add_filter("slugify", slugify);
+
+    // Encoding and hashing
+    env.add_filter("base64_encode", base64_encode);
+    env.add_filter("base64_decode", base64_decode);
+    env.add_filter("url_encode", url_encode);
+    env.add_filter("sha256", sha256);
+    env.add_filter("md5", md5);
+
+    // Text manipulation (`indent` is provided by MiniJinja itself)
+    env.add_filter("regex_replace", regex_replace);
+    env.add_filter("dedent", dedent);
+    env.add_filter("shell_quote", shell_quote);
+
+    // Random values
+    let uuid_rng = rng.clone();
+    env.add_function("uuid4", move || uuid4(&uuid_rng));
+    let int_rng = rng.clone();
+    env.add_function("random_int", move |min: i64, max: i64| {
+        random_int(&int_rng, min, max)
+    });
+    env.add_function("random_string", move |len: usize| random_string(&rng, len));
+}
+
+fn snake_case(value: &str) -> String {
+    value.to_snake_case()
+}
+
+fn camel_case(value: &str) -> String {
+    value.to_lower_camel_case()
+}
+
+fn kebab_case(value: &str) -> String {
+    value.to_kebab_case()
+}
+
+fn pascal_case(value: &str) -> String {
+    value.to_upper_camel_case()
+}
+
+/// Lowercase alphanumerics separated by single dashes; non-ASCII letters are kept
+fn slugify(value: &str) -> String {
+    value
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn base64_encode(value: &str) -> String {
+    STANDARD.encode(value)
+}
+
+fn base64_decode(value: &str) -> Result<String, Error> {
+    let bytes = STANDARD.decode(value.trim()).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidOperation,
+            format!("Invalid base64: {}", e),
+        )
+    })?;
+    String::from_utf8(bytes).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidOperation,
+            format!("Decoded base64 is not valid UTF-8: {}", e),
+        )
+    })
+}
+
+fn url_encode(value: &str) -> String {
+    urlencoding::encode(value).into_owned()
+}
+
+fn sha256(value: &str) -> String {
+    format!("{:x}", Sha256::digest(value.as_bytes()))
+}
+
+fn md5(value: &str) -> String {
+    format!("{:x}", Md5::digest(value.as_bytes()))
+}
+
+fn regex_replace(value: &str, pattern: &str, replacement: &str) -> Result<String, Error> {
+    let re = Regex::new(pattern)
+        .map_err(|e| Error::new(ErrorKind::InvalidOperation, format!("Invalid regex: {}", e)))?;
+    Ok(re.replace_all(value, replacement).into_owned())
+}
+
+fn lock(rng: &SharedRng) -> std::sync::MutexGuard<'_, StdRng> {
+    // A poisoned lock only means another render panicked, the RNG state is still usable
+    rng.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn uuid4(rng: &SharedRng) -> String {
+    let mut bytes = [0u8; 16];
+    lock(rng).fill_bytes(&mut bytes);
+    uuid::Builder::from_random_bytes(bytes)
+        .into_uuid()
+        .to_string()
+}
+
+fn random_int(rng: &SharedRng, min: i64, max: i64) -> Result<i64, Error> {
+    if min > max {
+        return Err(Error::new(
+            ErrorKind::InvalidOperation,
+            format!("random_int: min ({}) must not exceed max ({})", min, max),
+        ));
+    }
+    Ok(lock(rng).random_range(min..=max))
+}
+
+fn random_string(rng: &SharedRng, len: usize) -> String {
+    let mut rng = lock(rng);
+    (0..len).map(|_| rng.sample(Alphanumeric) as char).collect()
+}
@@ -5,29 +5,114 @@ use crossterm::{
     terminal::{Clear, ClearType},
 };
 use fuzzy_matcher::skim::SkimMatcherV2;
+use skim::reader::CommandCollector;
 use skim::{prelude::*, Skim};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
 use tracing::{debug, trace};
 use crate::domain::content::SnippetContent;
 use crate::domain::snippet::Snippet;
+use crate::domain::template::interface::TemplateEngine;
+use crate::infrastructure::highlight::{detect_language, Highlighter};
+
+/// Key switching the preview between template source and rendered output
+const TOGGLE_PREVIEW_KEY: &str = "alt-r";
+
+/// Reload command of the toggle key, handled by `PreviewToggle` instead of a shell
+const TOGGLE_PREVIEW_COMMAND: &str = "rsnip-toggle-preview";
+
+/// Optional preview features of the finder
+#[derive(Clone, Default)]
+pub struct PreviewOptions {
+    /// Allows toggling the preview to rendered output
+    pub engine: Option<Arc<dyn TemplateEngine>>,
+    /// Highlights previews according to the snippet language
+    pub highlighter: Option<Arc<Highlighter>>,
+    /// Concrete type of each snippet, in the order of the items, rendered with its variables
+    pub snippet_types: Vec<String>,
+}
+
+/// Preview settings shared by all items of a finder session
+struct PreviewState {
+    options: PreviewOptions,
+    show_rendered: Arc<AtomicBool>,
+}
 
 // Struct to hold snippet text, preview and source info
 #[derive(Clone)]
 struct SnippetItem {
     display_text: String,
-    preview: String,
+    header: String,
+    content: SnippetContent,
+    language: Option<String>,
+    snippet_type: String,
+    state: Arc<PreviewState>,
+    // Previews are built when the item is first highlighted
+    source: Arc<OnceLock<String>>,
+    rendered: Arc<OnceLock<String>>,
+}
+
+impl SnippetItem {
+    fn highlight(&self, text: &str) -> String {
+        match &self.state.options.highlighter {
+            Some(highlighter) => highlighter.highlight(text, self.language.as_deref()),
+            None => text.to_string(),
+        }
+    }
+
+    fn source_text(&self) -> &str {
+        self.source.get_or_init(|| {
+            let content = self.content.get_content();
+            format!(
+                "{}{}:\n{}",
+                self.header,
+                "Content".cyan().bold(),
+                if content.is_empty() {
+                    "No content".to_string()
+                } else {
+                    self.highlight(content)
+                }
+            )
+        })
+    }
+
+    /// Rendered output, computed when the item is first highlighted in rendered mode
+    fn rendered_text(&self, engine: &dyn TemplateEngine) -> &str {
+        self.rendered.get_or_init(|| {
+            let rendered = match engine.render_preview(&self.snippet_type, &self.content) {
+                Ok(text) if text.is_empty() => "No content".to_string(),
+                Ok(text) => self.highlight(&text),
+                Err(e) => e.to_string().red().to_string(),
+            };
+            format!("{}{}:\n{}", self.header, "Rendered".cyan().bold(), rendered)
+        })
+    }
 }
 
 impl SkimItem for SnippetItem {
-    fn text(&self) -> Cow<str> {
+    fn text(&self) -> Cow<'_, str> {
         Cow::Borrowed(&self.display_text)
     }
 
     fn preview(&self, _context: PreviewContext) -> ItemPreview {
-        ItemPreview::AnsiText(self.preview.clone())
+        let is_template = matches!(self.content, SnippetContent::Template { .. });
+        let preview = match &self.state.options.engine {
+            Some(engine) if self.state.show_rendered.load(Ordering::Relaxed) => format!(
+                "{}\n\n{}",
+                self.rendered_text(engine.as_ref()),
+                format!("{}: show source", TOGGLE_PREVIEW_KEY).dark_grey()
+            ),
+            Some(_) if is_template => format!(
+                "{}\n\n{}",
+                self.source_text(),
+                format!("{}: show rendered", TOGGLE_PREVIEW_KEY).dark_grey()
+            ),
+            _ => self.source_text().to_string(),
+        };
+        ItemPreview::AnsiText(preview)
     }
 
-    fn output(&self) -> Cow<str> {
+    fn output(&self) -> Cow<'_, str> {
         // self.text()
         // Extract just the name part before the tab and convert to owned string
         Cow::Owned(self.text().split('\t').next().unwrap_or("").to_string())
@@ -36,59 +121,91 @@ impl SkimItem for SnippetItem {
 
 /// Format snippets for display and preview
 pub fn create_skim_items(items: &[Snippet]) -> Vec<Arc<dyn SkimItem>> {
-    let mut snippet_items = Vec::new();
+    create_preview_skim_items(items, PreviewOptions::default(), Arc::new(AtomicBool::new(false)))
+}
 
-    for item in items {
-        // Get content as string
-        let content_str = match &item.content {
-            SnippetContent::Static(s) => s,
-            SnippetContent::Template { source, .. } => source,
-        };
+/// Format snippets for display with optional preview features.
+/// With an engine, the preview shows rendered output while `show_rendered` is set.
+pub fn create_preview_skim_items(
+    items: &[Snippet],
+    options: PreviewOptions,
+    show_rendered: Arc<AtomicBool>,
+) -> Vec<Arc<dyn SkimItem>> {
+    let state = Arc::new(PreviewState {
+        options,
+        show_rendered,
+    });
+    let mut snippet_items = Vec::new();
 
+    for (idx, item) in items.iter().enumerate() {
         let display_text = item.name.clone();
 
         // Format preview with colored headers and proper spacing
-        let preview = if item.comments.is_empty() {
-            format!(
-                "{}: {}\n\n{}:\n{}",
-                "Name".green().bold(),
-                item.name,
-                "Content".cyan().bold(),
-                if content_str.is_empty() {
-                    "No content"
-                } else {
-                    content_str
-                }
-            )
+        let header = if item.comments.is_empty() {
+            format!("{}: {}\n\n", "Name".green().bold(), item.name)
         } else {
             format!(
-                "{}: {}\n\n{}:\n{}\n\n{}:\n{}",
+                "{}: {}\n\n{}:\n{}\n\n",
                 "Name".green().bold(),
                 item.name,
                 "Comments".yellow().bold(),
                 item.comments.join("\n"), // Indent continuation lines
-                "Content".cyan().bold(),
-                if content_str.is_empty() {
-                    "No content"
-                } else {
-                    content_str
-                }
             )
         };
 
         snippet_items.push(Arc::new(SnippetItem {
             display_text,
-            preview,
+            header,
+            content: item.content.clone(),
+            language: detect_language(item),
+            snippet_type: state.options.snippet_types.get(idx).cloned().unwrap_or_default(),
+            state: state.clone(),
+            source: Arc::new(OnceLock::new()),
+            rendered: Arc::new(OnceLock::new()),
         }) as Arc<dyn SkimItem>);
     }
 
     snippet_items
 }
 
+/// Serves the items again when skim reloads them, switching the preview mode first.
+/// Reloading keeps the query and the cursor, unlike restarting skim.
+struct PreviewToggle {
+    items: Vec<Arc<dyn SkimItem>>,
+    show_rendered: Arc<AtomicBool>,
+}
+
+impl CommandCollector for PreviewToggle {
+    fn invoke(&mut self, cmd: &str, _components_to_stop: Arc<AtomicUsize>) -> (SkimItemReceiver, Sender<i32>) {
+        if cmd == TOGGLE_PREVIEW_COMMAND {
+            let rendered = !self.show_rendered.load(Ordering::Relaxed);
+            debug!("Toggling rendered preview: {}", rendered);
+            self.show_rendered.store(rendered, Ordering::Relaxed);
+        }
+
+        let (tx_item, rx_item): (SkimItemSender, SkimItemReceiver) = unbounded();
+        for item in &self.items {
+            let _ = tx_item.send(item.clone());
+        }
+        // Nothing runs in the background that could be interrupted
+        let (tx_interrupt, _) = bounded(1);
+        (rx_item, tx_interrupt)
+    }
+}
+
 /// Run fuzzy finder with multiline preview support and edit capability
 pub fn run_fuzzy_finder(
     items: &[Snippet],
     initial_query: &str,
+) -> Result<Option<String>> {
+    run_fuzzy_finder_with_preview(items, initial_query, PreviewOptions::default())
+}
+
+/// Run fuzzy finder; with an engine in `preview` the preview can be toggled to rendered output
+pub fn run_fuzzy_finder_with_preview(
+    items: &[Snippet],
+    initial_query: &str,
+    preview: PreviewOptions,
 ) -> Result<Option<String>> {
     debug!("Starting fuzzy finder with query: {}", initial_query);
 
@@ -125,20 +242,33 @@ pub fn run_fuzzy_finder(
         debug!("Launching UI with {} matches", matches.len());
     }
 
+    let show_rendered = Arc::new(AtomicBool::new(false));
+    let mut bindings = vec![
+        "ctrl-e:accept".to_string(),
+        "enter:accept".to_string(),
+        "ctrl-c:abort".to_string(),
+    ];
+    if preview.engine.is_some() {
+        bindings.push(format!("{}:reload({})", TOGGLE_PREVIEW_KEY, TOGGLE_PREVIEW_COMMAND));
+    }
+    let skim_items = create_preview_skim_items(items, preview, show_rendered.clone());
+    trace!("Items created: {}", skim_items.len());
+
+    let toggle = PreviewToggle {
+        items: skim_items.clone(),
+        show_rendered,
+    };
     let options = SkimOptionsBuilder::default()
         .height("20%".to_string())
         .layout("reverse".to_string()) // This puts the prompt at the bottom
         .multi(false)
         .margin("1,2".to_string()) // Add some margin
         .ansi(true)
-        .bind(vec![
-            "ctrl-e:accept".to_string(),
-            "enter:accept".to_string(),
-            "ctrl-c:abort".to_string(),
-        ])
+        .bind(bindings)
         // .inline_info(true)
         .preview_window("right:75%:wrap:border".to_string())
         .preview(Some("".to_string()))
+        .cmd_collector(Rc::new(RefCell::new(toggle)) as Rc<RefCell<dyn CommandCollector>>)
         // These three options are key for auto-selection:
         .filter(Some(initial_query.to_string())) // Immediately apply filter
         .query(Some(initial_query.to_string())) // Pre-populate search box
@@ -147,48 +277,44 @@ pub fn run_fuzzy_finder(
         .color(Some("dark,fg:252,bg:235,hl:178,fg+:252,bg+:237,hl+:178".to_string()))
         .build()?;
 
-    let skim_items = create_skim_items(items);
-    trace!("Items created: {}", skim_items.len());
-
     // Use unbounded channel to prevent potential deadlock
     let (tx_sink, rx_reader): (SkimItemSender, SkimItemReceiver) = unbounded();
-    for item in skim_items {
-        tx_sink.send(item)?;
+    for item in &skim_items {
+        tx_sink.send(item.clone())?;
     }
     drop(tx_sink); // Close sender after all items sent
 
     let mut stderr = std::io::stderr(); // this is the key for proper terminal cleanup
-    let selected = Skim::run_with(&options, Some(rx_reader))
-        .map(|out| {
-            // Always clean up terminal state after Skim closes, but seems not to be needed
-            execute!(stderr, Clear(ClearType::FromCursorDown)).ok();
-
-            match out.final_key {
-                Key::Ctrl('e') => {
-                    let selected_items = out
-                        .selected_items
-                        .iter()
-                        .filter_map(|selected_item| {
-                            (**selected_item)
-                                .as_any()
-                                .downcast_ref::<SnippetItem>()
-                                .map(|item| item.to_owned())
-                        })
-                        .collect::<Vec<SnippetItem>>();
-
-                    if let Some(item) = selected_items.first() {
-                        debug!("Opening editor for: {}", item.display_text);
-                    }
-                    None
-                }
-                Key::Enter => out
-                    .selected_items
-                    .first()
-                    .map(|item| item.output().to_string()),
-                _ => None,
+    let Some(out) = Skim::run_with(&options, Some(rx_reader)) else {
+        return Ok(None);
+    };
+    // Always clean up terminal state after Skim closes, but seems not to be needed
+    execute!(stderr, Clear(ClearType::FromCursorDown)).ok();
+
+    let selected = match out.final_key {
+        Key::Ctrl('e') => {
+            let selected_items = out
+                .selected_items
+                .iter()
+                .filter_map(|selected_item| {
+                    (**selected_item)
+                        .as_any()
+                        .downcast_ref::<SnippetItem>()
+                        .map(|item| item.to_owned())
+                })
+                .collect::<Vec<SnippetItem>>();
+
+            if let Some(item) = selected_items.first() {
+                debug!("Opening editor for: {}", item.display_text);
             }
-        })
-        .unwrap_or_default();
+            None
+        }
+        Key::Enter => out
+            .selected_items
+            .first()
+            .map(|item| item.output().to_string()),
+        _ => None,
+    };
 
     // Add additional terminal cleanup just in case
     // execute!(stdout, Clear(ClearType::FromCursorDown)).ok();
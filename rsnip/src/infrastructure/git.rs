@@ -0,0 +1,220 @@
+// infrastructure/git.rs
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, instrument, trace};
+
+/// Repository information exposed to templates as `git.*`
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct GitInfo {
+    pub branch: Option<String>,
+    pub root: PathBuf,
+    pub remote_url: Option<String>,
+    pub user_email: Option<String>,
+}
+
+impl GitInfo {
+    /// Discover the repository containing `start` by walking up to the first `.git` entry.
+    /// Reads HEAD and config files directly instead of spawning `git`.
+    #[instrument(level = "debug")]
+    pub fn discover(start: &Path) -> Option<Self> {
+        let root = start.ancestors().find(|dir| dir.join(".git").exists())?;
+        let git_dir = resolve_git_dir(&root.join(".git"))?;
+        debug!("Found git repository at {:?} (git dir {:?})", root, git_dir);
+
+        // Linked worktrees keep HEAD locally but share config with the main repository
+        let common_dir = fs::read_to_string(git_dir.join("commondir"))
+            .ok()
+            .map(|dir| git_dir.join(dir.trim()))
+            .unwrap_or_else(|| git_dir.clone());
+
+        let config = fs::read_to_string(common_dir.join("config"))
+            .map(|content| parse_git_config(&content))
+            .unwrap_or_default();
+
+        let remote_url = config.get("remote.origin.url").cloned().or_else(|| {
+            config
+                .iter()
+                .filter(|(key, _)| key.starts_with("remote.") && key.ends_with(".url"))
+                .min_by(|a, b| a.0.cmp(b.0))
+                .map(|(_, url)| url.clone())
+        });
+
+        let user_email = config
+            .get("user.email")
+            .cloned()
+            .or_else(global_user_email);
+
+        Some(Self {
+            branch: read_branch(&git_dir),
+            root: root.to_path_buf(),
+            remote_url,
+            user_email,
+        })
+    }
+}
+
+/// `.git` is either the git directory itself or a file pointing to it (worktrees, submodules)
+fn resolve_git_dir(dot_git: &Path) -> Option<PathBuf> {
+    if dot_git.is_dir() {
+        return Some(dot_git.to_path_buf());
+    }
+
+    let content = fs::read_to_string(dot_git).ok()?;
+    let target = content.trim().strip_prefix("gitdir:")?.trim();
+    let path = PathBuf::from(target);
+    if path.is_absolute() {
+        Some(path)
+    } else {
+        dot_git.parent().map(|parent| parent.join(path))
+    }
+}
+
+/// Branch name from HEAD, `None` for a detached HEAD
+fn read_branch(git_dir: &Path) -> Option<String> {
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref:")
+        .map(str::trim)
+        .map(|reference| {
+            reference
+                .strip_prefix("refs/heads/")
+                .unwrap_or(reference)
+                .to_string()
+        })
+}
+
+fn global_user_email() -> Option<String> {
+    let candidates = [
+        dirs::home_dir().map(|home| home.join(".gitconfig")),
+        dirs::config_dir().map(|config| config.join("git/config")),
+    ];
+
+    candidates
+        .into_iter()
+        .flatten()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .find_map(|content| parse_git_config(&content).remove("user.email"))
+}
+
+/// Minimal git config parser producing `section.subsection.key` entries.
+/// Section and key names are lowercased, subsections keep their case as git does.
+pub fn parse_git_config(content: &str) -> HashMap<String, String> {
+    let mut entries = HashMap::new();
+    let mut section = String::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = match header.split_once(char::is_whitespace) {
+                Some((name, subsection)) => format!(
+                    "{}.{}",
+                    name.to_lowercase(),
+                    subsection.trim().trim_matches('"')
+                ),
+                None => header.to_lowercase(),
+            };
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            let value = value.trim().trim_matches('"').to_string();
+            let key = format!("{}.{}", section, key.trim().to_lowercase());
+            trace!("git config: {} = {}", key, value);
+            entries.insert(key, value);
+        }
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_repo(head: &str, config: &str) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let git_dir = dir.path().join(".git");
+        fs::create_dir_all(&git_dir).unwrap();
+        fs::write(git_dir.join("HEAD"), head).unwrap();
+        fs::write(git_dir.join("config"), config).unwrap();
+        dir
+    }
+
+    #[test]
+    fn given_git_config_when_parsing_then_returns_dotted_keys() {
+        let content = r#"
+[core]
+    bare = false
+# comment
+[remote "origin"]
+    url = git@github.com:sysid/rsnip.git
+[User]
+    Email = dev@example.com
+"#;
+        let entries = parse_git_config(content);
+
+        assert_eq!(entries.get("core.bare").unwrap(), "false");
+        assert_eq!(
+            entries.get("remote.origin.url").unwrap(),
+            "git@github.com:sysid/rsnip.git"
+        );
+        assert_eq!(entries.get("user.email").unwrap(), "dev@example.com");
+    }
+
+    #[test]
+    fn given_nested_directory_when_discovering_then_finds_repository_root() {
+        let repo = create_repo(
+            "ref: refs/heads/feature/x\n",
+            "[remote \"origin\"]\n\turl = https://example.com/repo.git\n[user]\n\temail = me@example.com\n",
+        );
+        let nested = repo.path().join("src/deep");
+        fs::create_dir_all(&nested).unwrap();
+
+        let info = GitInfo::discover(&nested).unwrap();
+
+        assert_eq!(info.root, repo.path());
+        assert_eq!(info.branch.as_deref(), Some("feature/x"));
+        assert_eq!(
+            info.remote_url.as_deref(),
+            Some("https://example.com/repo.git")
+        );
+        assert_eq!(info.user_email.as_deref(), Some("me@example.com"));
+    }
+
+    #[test]
+    fn given_detached_head_when_discovering_then_branch_is_none() {
+        let repo = create_repo("3f2a1b0c9d8e7f6a5b4c3d2e1f0a9b8c7d6e5f4a\n", "");
+
+        let info = GitInfo::discover(repo.path()).unwrap();
+
+        assert!(info.branch.is_none());
+    }
+
+    #[test]
+    fn given_gitdir_file_when_discovering_then_follows_pointer() {
+        let main = create_repo("ref: refs/heads/main\n", "[remote \"upstream\"]\n\turl = u\n");
+        let worktree_git = main.path().join(".git/worktrees/wt");
+        fs::create_dir_all(&worktree_git).unwrap();
+        fs::write(worktree_git.join("HEAD"), "ref: refs/heads/wt-branch\n").unwrap();
+        fs::write(worktree_git.join("commondir"), "../..\n").unwrap();
+
+        let worktree = TempDir::new().unwrap();
+        fs::write(
+            worktree.path().join(".git"),
+            format!("gitdir: {}\n", worktree_git.display()),
+        )
+        .unwrap();
+
+        let info = GitInfo::discover(worktree.path()).unwrap();
+
+        assert_eq!(info.branch.as_deref(), Some("wt-branch"));
+        assert_eq!(info.remote_url.as_deref(), Some("u"));
+    }
+}
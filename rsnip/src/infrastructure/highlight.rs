@@ -0,0 +1,152 @@
+// infrastructure/highlight.rs
+use crate::domain::snippet::Snippet;
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
+use tracing::{debug, instrument};
+
+pub const DEFAULT_THEME: &str = "base16-ocean.dark";
+
+static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
+
+/// Highlights snippet content for terminal previews using the bundled syntaxes
+pub struct Highlighter {
+    theme: Theme,
+}
+
+impl Highlighter {
+    /// Create a highlighter with one of the bundled themes, `DEFAULT_THEME` if `None`
+    pub fn new(theme: Option<&str>) -> Result<Self> {
+        let name = theme.unwrap_or(DEFAULT_THEME);
+        let theme = THEME_SET.themes.get(name).cloned().ok_or_else(|| {
+            anyhow!(
+                "Unknown preview theme '{}', available: {}",
+                name,
+                theme_names().join(", ")
+            )
+        })?;
+        Ok(Self { theme })
+    }
+
+    /// Highlight `text` as `language`; unknown languages are returned unchanged
+    #[instrument(level = "trace", skip(self, text))]
+    pub fn highlight(&self, text: &str, language: Option<&str>) -> String {
+        let Some(syntax) = language.and_then(find_syntax) else {
+            return text.to_string();
+        };
+
+        let mut lines = HighlightLines::new(syntax, &self.theme);
+        let mut highlighted = String::new();
+        for line in LinesWithEndings::from(text) {
+            match lines.highlight_line(line, &SYNTAX_SET) {
+                Ok(ranges) => highlighted.push_str(&as_24_bit_terminal_escaped(&ranges, false)),
+                Err(e) => {
+                    debug!("Highlighting failed, showing plain text: {}", e);
+                    return text.to_string();
+                }
+            }
+        }
+        // Reset colors so they don't leak into the rest of the preview
+        highlighted.push_str("\x1b[0m");
+        highlighted
+    }
+}
+
+/// Names of the bundled themes
+pub fn theme_names() -> Vec<&'static str> {
+    THEME_SET.themes.keys().map(String::as_str).collect()
+}
+
+/// Language of a snippet: its own metadata, then the language of a Markdown code fence
+pub fn detect_language(snippet: &Snippet) -> Option<String> {
+    snippet.language.clone().or_else(|| {
+        snippet
+            .content
+            .get_content()
+            .trim_start()
+            .strip_prefix("```")
+            .and_then(|rest| rest.lines().next())
+            .map(str::trim)
+            .filter(|language| !language.is_empty())
+            .map(str::to_string)
+    })
+}
+
+/// Find a syntax by name or file extension, accepting common editor language ids
+fn find_syntax(language: &str) -> Option<&'static SyntaxReference> {
+    let token = match language.to_lowercase().as_str() {
+        "shellscript" | "sh" | "zsh" | "shell" => "bash".to_string(),
+        "golang" => "go".to_string(),
+        "yml" => "yaml".to_string(),
+        "py" => "python".to_string(),
+        other => other.to_string(),
+    };
+    SYNTAX_SET
+        .find_syntax_by_token(&token)
+        .or_else(|| SYNTAX_SET.find_syntax_by_extension(&token))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::content::SnippetContent;
+
+    fn snippet(content: &str, language: Option<&str>) -> Snippet {
+        Snippet {
+            name: "test".to_string(),
+            content: SnippetContent::new(content.to_string()),
+            comments: Vec::new(),
+            language: language.map(str::to_string),
+            output: None,
+        }
+    }
+
+    #[test]
+    fn given_known_language_when_highlighting_then_adds_ansi_colors() {
+        let highlighter = Highlighter::new(None).unwrap();
+
+        let result = highlighter.highlight("fn main() {}\n", Some("rust"));
+
+        assert!(result.contains("\x1b[38;2;"));
+        assert!(result.contains("main"));
+    }
+
+    #[test]
+    fn given_unknown_or_missing_language_when_highlighting_then_returns_plain_text() {
+        let highlighter = Highlighter::new(None).unwrap();
+
+        assert_eq!(highlighter.highlight("text", Some("klingon")), "text");
+        assert_eq!(highlighter.highlight("text", None), "text");
+    }
+
+    #[test]
+    fn given_editor_language_ids_when_finding_syntax_then_maps_aliases() {
+        assert_eq!(find_syntax("shellscript").unwrap().name, "Bourne Again Shell (bash)");
+        assert_eq!(find_syntax("Python").unwrap().name, "Python");
+        assert_eq!(find_syntax("rs").unwrap().name, "Rust");
+    }
+
+    #[test]
+    fn given_metadata_or_fence_when_detecting_language_then_prefers_metadata() {
+        assert_eq!(
+            detect_language(&snippet("```python\nprint(1)\n```", Some("rust"))).as_deref(),
+            Some("rust")
+        );
+        assert_eq!(
+            detect_language(&snippet("```python\nprint(1)\n```", None)).as_deref(),
+            Some("python")
+        );
+        assert_eq!(detect_language(&snippet("plain", None)), None);
+    }
+
+    #[test]
+    fn given_unknown_theme_when_creating_then_lists_available_themes() {
+        let err = Highlighter::new(Some("nope")).err().unwrap();
+
+        assert!(err.to_string().contains(DEFAULT_THEME));
+    }
+}
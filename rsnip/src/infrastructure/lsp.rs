@@ -0,0 +1,280 @@
+// infrastructure/lsp.rs
+use crate::domain::editor::{EditorSnippet, SnippetProvider};
+use anyhow::{anyhow, Context, Result};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{DidCloseTextDocument, DidOpenTextDocument, Notification as _};
+use lsp_types::request::{Completion, Request as _, ResolveCompletionItem};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionOptions,
+    CompletionParams, CompletionResponse, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    Documentation, InitializeResult, InsertTextFormat, MarkupContent, MarkupKind,
+    ServerCapabilities, ServerInfo, TextDocumentSyncCapability, TextDocumentSyncKind,
+    TextDocumentSyncOptions, Uri,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::{debug, info, instrument};
+
+/// Identifies the snippet of a completion item between `completion` and `completionItem/resolve`
+#[derive(Debug, Serialize, Deserialize)]
+struct ItemData {
+    ctype: String,
+    name: String,
+}
+
+/// Serve snippet completions over stdin and stdout until the client exits
+pub fn serve_stdio(provider: &dyn SnippetProvider) -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    serve(connection, provider)?;
+    io_threads.join()?;
+    Ok(())
+}
+
+/// Serve snippet completions on `connection` until the client shuts the server down or leaves
+pub fn serve(connection: Connection, provider: &dyn SnippetProvider) -> Result<()> {
+    let (id, _) = connection.initialize_start()?;
+    let initialize = InitializeResult {
+        capabilities: capabilities(),
+        server_info: Some(ServerInfo {
+            name: "rsnip".to_string(),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        }),
+    };
+    connection.initialize_finish(id, serde_json::to_value(initialize)?)?;
+    info!("Language server initialized");
+
+    let mut server = LanguageServer::new(provider);
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                connection.sender.send(server.handle_request(request).into())?;
+            }
+            Message::Notification(notification) => server.handle_notification(notification),
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        // Only the language of open documents matters, not their text
+        text_document_sync: Some(TextDocumentSyncCapability::Options(TextDocumentSyncOptions {
+            open_close: Some(true),
+            change: Some(TextDocumentSyncKind::NONE),
+            ..Default::default()
+        })),
+        completion_provider: Some(CompletionOptions {
+            resolve_provider: Some(true),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+struct LanguageServer<'a> {
+    provider: &'a dyn SnippetProvider,
+    /// Language ids of open documents
+    languages: HashMap<Uri, String>,
+}
+
+impl<'a> LanguageServer<'a> {
+    fn new(provider: &'a dyn SnippetProvider) -> Self {
+        Self {
+            provider,
+            languages: HashMap::new(),
+        }
+    }
+
+    fn handle_notification(&mut self, notification: Notification) {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                if let Ok(params) =
+                    serde_json::from_value::<DidOpenTextDocumentParams>(notification.params)
+                {
+                    let document = params.text_document;
+                    self.languages.insert(document.uri, document.language_id);
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                if let Ok(params) =
+                    serde_json::from_value::<DidCloseTextDocumentParams>(notification.params)
+                {
+                    self.languages.remove(&params.text_document.uri);
+                }
+            }
+            method => debug!("Ignoring notification {}", method),
+        }
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        let result = match request.method.as_str() {
+            Completion::METHOD => self.completion(request.params),
+            ResolveCompletionItem::METHOD => self.resolve(request.params),
+            method => {
+                return Response::new_err(
+                    request.id,
+                    ErrorCode::MethodNotFound as i32,
+                    format!("Unsupported method {}", method),
+                )
+            }
+        };
+        match result {
+            Ok(value) => Response::new_ok(request.id, value),
+            Err(e) => Response::new_err(request.id, ErrorCode::InternalError as i32, format!("{:#}", e)),
+        }
+    }
+
+    /// Snippets matching the language of the document, all if it is not open
+    #[instrument(level = "debug", skip(self, params))]
+    fn completion(&self, params: serde_json::Value) -> Result<serde_json::Value> {
+        let params: CompletionParams = serde_json::from_value(params)?;
+        let uri = &params.text_document_position.text_document.uri;
+        let language = self.languages.get(uri).map(String::as_str);
+
+        let items: Vec<CompletionItem> = self
+            .provider
+            .snippets(language)?
+            .into_iter()
+            .map(completion_item)
+            .collect();
+        debug!("Offering {} snippets for {:?}", items.len(), language);
+        Ok(serde_json::to_value(CompletionResponse::Array(items))?)
+    }
+
+    /// Render the item's snippet into its insert text. Template errors are shown as the
+    /// item's documentation, the editor then inserts the label.
+    #[instrument(level = "debug", skip(self, params))]
+    fn resolve(&self, params: serde_json::Value) -> Result<serde_json::Value> {
+        let mut item: CompletionItem = serde_json::from_value(params)?;
+        let data: ItemData = item
+            .data
+            .clone()
+            .map(serde_json::from_value)
+            .transpose()?
+            .ok_or_else(|| anyhow!("Completion item '{}' is not a snippet", item.label))?;
+
+        let text = self
+            .provider
+            .resolve(&data.ctype, &data.name)
+            .with_context(|| format!("Failed to render snippet '{}/{}'", data.ctype, data.name))?;
+        item.insert_text = Some(text);
+        item.insert_text_format = Some(InsertTextFormat::SNIPPET);
+        Ok(serde_json::to_value(item)?)
+    }
+}
+
+fn completion_item(snippet: EditorSnippet) -> CompletionItem {
+    let source = format!(
+        "```{}\n{}\n```",
+        snippet.language.as_deref().unwrap_or_default(),
+        snippet.source.trim_end()
+    );
+    CompletionItem {
+        data: Some(serde_json::json!({ "ctype": snippet.ctype, "name": snippet.name })),
+        label_details: Some(CompletionItemLabelDetails {
+            detail: None,
+            description: Some(snippet.ctype),
+        }),
+        label: snippet.name,
+        kind: Some(CompletionItemKind::SNIPPET),
+        detail: snippet.description,
+        documentation: Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: source,
+        })),
+        insert_text: snippet.insert_text,
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_server::RequestId;
+
+    /// Offers a static snippet and a template that fails to render
+    struct FakeProvider;
+
+    impl SnippetProvider for FakeProvider {
+        fn snippets(&self, language: Option<&str>) -> Result<Vec<EditorSnippet>> {
+            let snippet = |name: &str, insert_text: Option<&str>| EditorSnippet {
+                ctype: "shell".to_string(),
+                name: name.to_string(),
+                description: language.map(str::to_string),
+                language: None,
+                source: name.to_string(),
+                insert_text: insert_text.map(str::to_string),
+            };
+            Ok(vec![snippet("ll", Some("ls -l")), snippet("broken", None)])
+        }
+
+        fn resolve(&self, _ctype: &str, name: &str) -> Result<String> {
+            Err(anyhow!("unknown filter in '{}'", name))
+        }
+    }
+
+    fn request(id: i32, method: &str, params: serde_json::Value) -> Request {
+        Request::new(RequestId::from(id), method.to_string(), params)
+    }
+
+    #[test]
+    fn given_open_document_when_completing_then_passes_its_language() {
+        let mut server = LanguageServer::new(&FakeProvider);
+        server.handle_notification(Notification::new(
+            DidOpenTextDocument::METHOD.to_string(),
+            serde_json::json!({ "textDocument": {
+                "uri": "file:///tmp/a.sh", "languageId": "shellscript", "version": 1, "text": ""
+            }}),
+        ));
+
+        let response = server.handle_request(request(
+            1,
+            Completion::METHOD,
+            serde_json::json!({
+                "textDocument": { "uri": "file:///tmp/a.sh" },
+                "position": { "line": 0, "character": 0 }
+            }),
+        ));
+
+        let items: Vec<CompletionItem> = serde_json::from_value(response.result.unwrap()).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].label, "ll");
+        assert_eq!(items[0].detail.as_deref(), Some("shellscript"));
+        assert_eq!(items[0].insert_text.as_deref(), Some("ls -l"));
+        assert_eq!(items[1].insert_text, None);
+    }
+
+    #[test]
+    fn given_failing_template_when_resolving_then_responds_with_the_error() {
+        let server = LanguageServer::new(&FakeProvider);
+        let item = completion_item(FakeProvider.snippets(None).unwrap().remove(1));
+
+        let response = server.handle_request(request(
+            2,
+            ResolveCompletionItem::METHOD,
+            serde_json::to_value(item).unwrap(),
+        ));
+
+        assert_eq!(response.result, None);
+        let error = response.error.unwrap();
+        assert_eq!(error.code, ErrorCode::InternalError as i32);
+        assert_eq!(
+            error.message,
+            "Failed to render snippet 'shell/broken': unknown filter in 'broken'"
+        );
+    }
+
+    #[test]
+    fn given_unsupported_method_when_handling_then_responds_method_not_found() {
+        let server = LanguageServer::new(&FakeProvider);
+
+        let response = server.handle_request(request(3, "textDocument/hover", serde_json::json!({})));
+
+        assert_eq!(response.error.unwrap().code, ErrorCode::MethodNotFound as i32);
+    }
+}
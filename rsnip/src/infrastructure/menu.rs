@@ -0,0 +1,130 @@
+// infrastructure/menu.rs
+use crate::domain::menu::MenuEntry;
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use tracing::{debug, instrument};
+
+/// Marks the line showing an error in a dmenu-compatible menu
+pub const ERROR_MARK: &str = "! ";
+
+/// Rows for rofi's script mode: the prompt and an optional message, then one row per
+/// entry carrying its label as `info`, which rofi passes back in `ROFI_INFO`
+pub fn rofi_rows(entries: &[MenuEntry], message: Option<&str>) -> String {
+    let mut rows = String::from("\0prompt\x1fsnippet\n");
+    if let Some(message) = message {
+        rows.push_str(&format!("\0message\x1f{}\n", escape_markup(&single_line(message))));
+    }
+    for entry in entries {
+        rows.push_str(&format!("{}\0info\x1f{}\n", entry.display(), entry.label()));
+    }
+    rows
+}
+
+/// Lines for dmenu-compatible launchers, led by an error if there is one
+pub fn dmenu_lines(entries: &[MenuEntry], error: Option<&str>) -> String {
+    let mut lines = String::new();
+    if let Some(error) = error {
+        lines.push_str(&format!("{}{}\n", ERROR_MARK, single_line(error)));
+    }
+    for entry in entries {
+        lines.push_str(&entry.display());
+        lines.push('\n');
+    }
+    lines
+}
+
+/// Run the dmenu-compatible `command` through the shell with `lines` as choices.
+/// Returns the selected line, `None` if the menu was cancelled.
+#[instrument(level = "debug", skip(lines))]
+pub fn run_dmenu(command: &str, lines: &str) -> Result<Option<String>> {
+    let mut child = Command::new("sh")
+        .args(["-c", command])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run menu '{}'", command))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // The menu may exit before reading all choices
+        let _ = stdin.write_all(lines.as_bytes());
+    }
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("Failed to run menu '{}'", command))?;
+    debug!("Menu exited with {}", output.status);
+
+    let selected = String::from_utf8_lossy(&output.stdout)
+        .trim_end_matches(['\n', '\r'])
+        .to_string();
+    Ok((output.status.success() && !selected.is_empty()).then_some(selected))
+}
+
+/// Menus are line based, so messages spanning lines are joined
+fn single_line(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Rofi renders messages as Pango markup
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<MenuEntry> {
+        vec![
+            MenuEntry {
+                ctype: "shell".to_string(),
+                name: "gc".to_string(),
+                description: Some("Commit all".to_string()),
+            },
+            MenuEntry {
+                ctype: "shell".to_string(),
+                name: "ll".to_string(),
+                description: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn given_entries_and_error_when_listing_for_rofi_then_rows_carry_info_and_message() {
+        let rows = rofi_rows(&entries(), Some("unknown filter 'x'\n  at <line 1>"));
+
+        assert_eq!(
+            rows,
+            "\0prompt\x1fsnippet\n\
+             \0message\x1funknown filter 'x' at &lt;line 1&gt;\n\
+             shell/gc  Commit all\0info\x1fshell/gc\n\
+             shell/ll\0info\x1fshell/ll\n"
+        );
+    }
+
+    #[test]
+    fn given_error_when_listing_for_dmenu_then_error_leads() {
+        assert_eq!(
+            dmenu_lines(&entries(), Some("broken")),
+            "! broken\nshell/gc  Commit all\nshell/ll\n"
+        );
+        assert_eq!(dmenu_lines(&[], None), "");
+    }
+
+    #[test]
+    fn given_menu_command_when_running_then_returns_selection_or_none() {
+        let lines = dmenu_lines(&entries(), None);
+
+        assert_eq!(
+            run_dmenu("tail -n 1", &lines).unwrap(),
+            Some("shell/ll".to_string())
+        );
+        assert_eq!(run_dmenu("cat >/dev/null; exit 1", &lines).unwrap(), None);
+    }
+}
@@ -3,86 +3,882 @@ use crate::domain::{
     content::SnippetContent,
     template::{
         errors::TemplateError,
-        interface::{ShellCommandExecutor, TemplateEngine},
+        interface::{
+            ShellCommandExecutor, SnippetResolver, TemplateEngine, UserPrompter, VariableResolver,
+        },
+        model::TemplateIssue,
     },
 };
-use chrono::{DateTime, Utc};
-use minijinja::{Environment, Error, ErrorKind, Value};
-use std::collections::HashMap;
+use crate::infrastructure::clipboard::get_clipboard_text;
+use crate::infrastructure::dates::{self, Clock};
+use crate::infrastructure::env_vars::{self, EnvPolicy};
+use crate::infrastructure::filters;
+use crate::infrastructure::git::GitInfo;
+use crate::infrastructure::prompts::{self, is_preview, PromptSession, TerminalPrompter, PREVIEW};
+use gethostname::gethostname;
+use minijinja::value::{Enumerator, Object, ObjectRepr, Rest};
+use minijinja::{Environment, Error, ErrorKind, State, Template, Value};
+use once_cell::sync::Lazy;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use regex::Regex;
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use tracing::{info};
+use std::sync::{
+    Arc, Mutex, MutexGuard, OnceLock, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard,
+};
+use std::time::SystemTime;
+use tracing::{debug, info};
+
+static MACRO_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\{%-?\s*macro\s+(\w+)").expect("Failed to compile macro regex")
+});
+
+/// Expression and statement blocks, with raw sections matched separately so they can be skipped
+static BLOCK_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?s)\{%-?\s*raw\s*-?%\}.*?\{%-?\s*endraw\s*-?%\}|\{\{.*?\}\}|\{%.*?%\}")
+        .expect("Failed to compile block regex")
+});
+
+static STRING_LITERAL_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"'[^']*'|"[^"]*""#).expect("Failed to compile string literal regex")
+});
+
+static FILTER_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\|\s*([A-Za-z_]\w*)|^\{%-?\s*filter\s+([A-Za-z_]\w*)")
+        .expect("Failed to compile filter regex")
+});
+
+static SHELL_LITERAL_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:'([^']*)'|"([^"]*)")\s*\|\s*shell\b"#)
+        .expect("Failed to compile shell literal regex")
+});
+
+/// Name prefix of snippet templates compiled by the engine, followed by the content hash
+const COMPILED_PREFIX: &str = "__snippet_";
+
+/// Snippet templates kept compiled at most; the ones compiled first are dropped beyond
+const MAX_COMPILED_TEMPLATES: usize = 256;
+
+/// Sources served to the environment's loader, which compiles and keeps each one it loads
+#[derive(Default)]
+struct TemplateCache {
+    /// Sources of compiled snippet templates by name
+    snippets: HashMap<String, String>,
+    /// Names of compiled snippet templates, compiled first at the front
+    order: VecDeque<String>,
+    /// Sources of referenced snippets as the resolver returned them, to notice edits
+    references: HashMap<String, String>,
+    /// Number of sources loaded, i.e. compiled, by the environment
+    loads: usize,
+}
+
+/// Template library as loaded, to reload it when the file is modified
+struct LibraryFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    /// Macros and variables registered from the library
+    exports: Vec<String>,
+}
+
+/// Context variable holding the clipboard text, read only when a template looks it up
+const CLIPBOARD: &str = "clipboard";
+
+/// Variables `create_context` provides besides user-defined ones, `git` only inside a repository
+const CONTEXT_VARIABLES: &[&str] = &[
+    "current_date",
+    "cwd",
+    "cwd_name",
+    "git",
+    "hostname",
+    "user",
+    "os",
+    "arch",
+    CLIPBOARD,
+];
+
+/// Names provided by MiniJinja inside loops, macros and blocks
+const SPECIAL_VARIABLES: &[&str] = &["loop", "caller", "varargs", "kwargs", "self", "super"];
+
+thread_local! {
+    // Referenced snippets currently being rendered, innermost last
+    static SNIPPET_CHAIN: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    // Chain to the last referenced snippet the resolver did not find
+    static MISSING_SNIPPET: RefCell<Option<String>> = const { RefCell::new(None) };
+}
 
 pub struct MiniJinjaEngine {
-    env: Environment<'static>,
+    env: RwLock<Environment<'static>>,
+    shell_executor: Box<dyn ShellCommandExecutor>,
+    clock: Clock,
+    resolver: Option<Arc<dyn SnippetResolver>>,
+    cache: Arc<Mutex<TemplateCache>>,
+    library: Mutex<Option<LibraryFile>>,
+    type_vars: Option<Arc<dyn VariableResolver>>,
+    vars: HashMap<String, Value>,
+    env_policy: EnvPolicy,
+    legacy_env_vars: bool,
+    prompter: Arc<dyn UserPrompter>,
+    answers: HashMap<String, String>,
+    prompts: Arc<PromptSession>,
 }
 
 impl MiniJinjaEngine {
     pub fn new(shell_executor: Box<dyn ShellCommandExecutor>) -> Self {
         let mut env = Environment::new();
 
-        // Register template filters
-        env.add_filter("strftime", date_format);
-        env.add_filter("subtract_days", subtract_days);
-        env.add_filter("add_days", add_days);
+        // Register date filters
+        let clock = Clock::default();
+        dates::register(&mut env, clock.clone());
 
-        // Create shell filter with captured executor
+        // Register env() with secrets hidden by default
+        let env_policy = EnvPolicy::default();
+        env_vars::register(&mut env, env_policy.clone());
+
+        // Register standard filter and function library
+        filters::register(&mut env, Arc::new(Mutex::new(StdRng::from_os_rng())));
+
+        // Create shell filter with captured executor, previews run each command only once
         let shell_executor_clone = shell_executor.box_clone();
-        env.add_filter("shell", move |value: Value| {
+        let preview_outputs: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+        env.add_filter("shell", move |state: &State, value: Value| {
             let cmd = value.as_str().ok_or_else(|| {
                 Error::new(ErrorKind::InvalidOperation, "Expected string command")
             })?;
 
+            let preview = is_preview(state);
+            if preview {
+                let outputs = preview_outputs.lock().unwrap_or_else(|e| e.into_inner());
+                if let Some(output) = outputs.get(cmd) {
+                    return Ok(Value::from(output.clone()));
+                }
+            }
+
             match shell_executor_clone.execute(cmd) {
-                Ok(result) => Ok(Value::from(result)),
+                Ok(result) => {
+                    if preview {
+                        preview_outputs
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .insert(cmd.to_string(), result.clone());
+                    }
+                    Ok(Value::from(result))
+                }
                 Err(e) => Err(Error::new(ErrorKind::InvalidOperation, e.to_string())),
             }
         });
 
-        Self {
-            env,
+        env.add_function("snippet", render_snippet);
+        env.add_function(ENTER_SNIPPET, enter_snippet);
+        env.add_function(LEAVE_SNIPPET, leave_snippet);
+
+        // Register interactive input functions
+        let prompter: Arc<dyn UserPrompter> = Arc::new(TerminalPrompter);
+        let prompts = Arc::new(PromptSession::new(prompter.clone(), HashMap::new()));
+        prompts::register(&mut env, prompts.clone());
+
+        let mut engine = Self {
+            env: RwLock::new(env),
+            shell_executor,
+            clock,
+            resolver: None,
+            cache: Arc::new(Mutex::new(TemplateCache::default())),
+            library: Mutex::new(None),
+            type_vars: None,
+            vars: HashMap::new(),
+            env_policy,
+            legacy_env_vars: false,
+            prompter,
+            answers: HashMap::new(),
+            prompts,
+        };
+        engine.install_loader();
+        engine
+    }
+
+    /// Allow templates to include, import and call other snippets via `resolver`
+    pub fn with_snippet_resolver(mut self, resolver: Arc<dyn SnippetResolver>) -> Self {
+        self.resolver = Some(resolver);
+        self.install_loader();
+        self
+    }
+
+    /// Serve compiled snippet templates first, then referenced snippets from the resolver.
+    /// The environment caches everything loaded, so each source is compiled only once.
+    /// Referenced snippets are wrapped to track the chain of snippets being rendered,
+    /// which reports cycles and missing snippets along with the snippets leading to them.
+    fn install_loader(&mut self) {
+        let cache = self.cache.clone();
+        let resolver = self.resolver.clone();
+        self.env_mut().set_loader(move |name| {
+            {
+                let mut cache = lock(&cache);
+                if let Some(source) = cache.snippets.get(name).cloned() {
+                    cache.loads += 1;
+                    return Ok(Some(source));
+                }
+            }
+            let Some(resolver) = &resolver else {
+                return Ok(None);
+            };
+            match resolver.resolve(name) {
+                Ok(Some(source)) => {
+                    let tracked = track_snippet(name, &source);
+                    let mut cache = lock(&cache);
+                    cache.references.insert(name.to_string(), source);
+                    cache.loads += 1;
+                    Ok(Some(tracked))
+                }
+                Ok(None) => {
+                    let chain = chain_to(name);
+                    MISSING_SNIPPET.with(|missing| missing.replace(Some(chain)));
+                    Ok(None)
+                }
+                Err(e) => Err(Error::new(ErrorKind::TemplateNotFound, e.to_string())),
+            }
+        });
+    }
+
+    /// Call `f` with the compiled template for `source`, reused as long as the source is
+    /// unchanged and among the last `MAX_COMPILED_TEMPLATES` compiled
+    fn with_template<R>(&self, source: &str, f: impl FnOnce(&Template) -> R) -> Result<R, Error> {
+        let mut hasher = DefaultHasher::new();
+        source.hash(&mut hasher);
+        let name = format!("{}{:016x}", COMPILED_PREFIX, hasher.finish());
+
+        let (cached, evicted) = {
+            let mut cache = lock(&self.cache);
+            match cache.snippets.get(&name) {
+                Some(cached) => (cached == source, None),
+                None => {
+                    debug!("Compiling template {}", name);
+                    cache.snippets.insert(name.clone(), source.to_string());
+                    cache.order.push_back(name.clone());
+                    let evicted = if cache.order.len() > MAX_COMPILED_TEMPLATES {
+                        cache.order.pop_front()
+                    } else {
+                        None
+                    };
+                    if let Some(evicted) = &evicted {
+                        cache.snippets.remove(evicted);
+                    }
+                    (true, evicted)
+                }
+            }
+        };
+        if let Some(evicted) = evicted {
+            debug!("Dropping compiled template {}", evicted);
+            write(&self.env).remove_template(&evicted);
+        }
+
+        let env = read(&self.env);
+        let template = if cached {
+            env.get_template(&name)?
+        } else {
+            // Hash collision with a different source, compile without caching
+            env.template_from_str(source)?
+        };
+        Ok(f(&template))
+    }
+
+    /// Drop referenced snippets whose source changed and reload the template library if its
+    /// file was modified, so a long-running process like `rsnip lsp` picks up edits
+    fn refresh(&self) -> Result<(), TemplateError> {
+        let stale: Vec<String> = match &self.resolver {
+            Some(resolver) => {
+                let references = lock(&self.cache).references.clone();
+                references
+                    .into_iter()
+                    .filter(|(reference, source)| {
+                        !matches!(resolver.resolve(reference), Ok(Some(current)) if current == *source)
+                    })
+                    .map(|(reference, _)| reference)
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+        let mut library = lock(&self.library);
+        let library_changed = library
+            .as_ref()
+            .is_some_and(|library| modified_time(&library.path) != library.modified);
+        if stale.is_empty() && !library_changed {
+            return Ok(());
+        }
+
+        let mut env = write(&self.env);
+        for reference in stale {
+            debug!("Dropping changed snippet {}", reference);
+            env.remove_template(&reference);
+            lock(&self.cache).references.remove(&reference);
+        }
+        if let Some(library) = library.as_mut().filter(|_| library_changed) {
+            library.modified = modified_time(&library.path);
+            for export in library.exports.drain(..) {
+                env.remove_global(&export);
+            }
+            library.exports = register_library(&mut env, &library.path)?;
+            info!("Reloaded template library: {}", library.path.display());
         }
+        Ok(())
+    }
+
+    fn env_mut(&mut self) -> &mut Environment<'static> {
+        self.env.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Use `clock` for `current_date` and for resolving dates in filters
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        dates::register(self.env_mut(), clock.clone());
+        self.clock = clock;
+        self
+    }
+
+    /// Seed the random source of `uuid4`, `random_int` and `random_string` for reproducible output
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        filters::register(self.env_mut(), Arc::new(Mutex::new(StdRng::seed_from_u64(seed))));
+        self
+    }
+
+    /// Restrict which environment variables `env()` and legacy `env_*` variables expose
+    pub fn with_env_policy(mut self, policy: EnvPolicy) -> Self {
+        env_vars::register(self.env_mut(), policy.clone());
+        self.env_policy = policy;
+        self
+    }
+
+    /// Also expose visible environment variables as `env_NAME` context variables (pre-`env()` behaviour)
+    pub fn with_legacy_env_vars(mut self, enabled: bool) -> Self {
+        self.legacy_env_vars = enabled;
+        self
+    }
+
+    /// Ask `prompt()`, `confirm()` and `choose()` questions through `prompter`
+    pub fn with_prompter(mut self, prompter: Arc<dyn UserPrompter>) -> Self {
+        self.prompter = prompter;
+        self.register_prompts();
+        self
+    }
+
+    /// Preset answers by question label, used instead of asking
+    pub fn with_answers<I, K, V>(mut self, answers: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.answers.extend(
+            answers
+                .into_iter()
+                .map(|(label, answer)| (label.into(), answer.into())),
+        );
+        self.register_prompts();
+        self
+    }
+
+    fn register_prompts(&mut self) {
+        self.prompts = Arc::new(PromptSession::new(
+            self.prompter.clone(),
+            self.answers.clone(),
+        ));
+        let session = self.prompts.clone();
+        prompts::register(self.env_mut(), session);
+    }
+
+    /// Look up variables of the type of each rendered snippet through `resolver`
+    pub fn with_variable_resolver(mut self, resolver: Arc<dyn VariableResolver>) -> Self {
+        self.type_vars = Some(resolver);
+        self
+    }
+
+    /// Add user-defined variables to the render context; later calls override earlier ones,
+    /// and all override the variables of the snippet type
+    pub fn with_vars<I, K>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, serde_json::Value)>,
+        K: Into<String>,
+    {
+        self.vars.extend(
+            vars.into_iter()
+                .map(|(key, value)| (key.into(), Value::from_serialize(&value))),
+        );
+        self
     }
 
-    fn create_context(&self) -> HashMap<String, Value> {
+    /// Load a shared macro file and expose its macros and top-level variables to every snippet.
+    /// Errors carry the library file and line so they can be reported once at startup.
+    /// The library is reloaded when the file is modified.
+    pub fn load_template_library(&mut self, path: &Path) -> Result<(), TemplateError> {
+        let modified = modified_time(path);
+        let exports = register_library(self.env_mut(), path)?;
+        *lock(&self.library) = Some(LibraryFile {
+            path: path.to_path_buf(),
+            modified,
+            exports,
+        });
+
+        info!("Loaded template library: {}", path.display());
+        Ok(())
+    }
+
+    fn create_context(&self, snippet_type: &str) -> HashMap<String, Value> {
         let mut context = HashMap::new();
 
         // Add current date/time
         context.insert(
             "current_date".to_string(),
-            Value::from(Utc::now().to_rfc3339()),
+            Value::from(self.clock.now().to_rfc3339()),
         );
 
-        // Add environment variables
-        for (key, value) in std::env::vars() {
-            context.insert(format!("env_{}", key), Value::from(value));
+        // Add working directory and system information
+        if let Ok(cwd) = std::env::current_dir() {
+            let cwd_name = cwd
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            context.insert("cwd".to_string(), Value::from(cwd.display().to_string()));
+            context.insert("cwd_name".to_string(), Value::from(cwd_name));
+
+            if let Some(git) = GitInfo::discover(&cwd) {
+                context.insert("git".to_string(), Value::from_serialize(&git));
+            }
+        }
+        context.insert(
+            "hostname".to_string(),
+            Value::from(gethostname().to_string_lossy().to_string()),
+        );
+        context.insert(
+            "user".to_string(),
+            Value::from(
+                std::env::var("USER")
+                    .or_else(|_| std::env::var("USERNAME"))
+                    .unwrap_or_default(),
+            ),
+        );
+        context.insert("os".to_string(), Value::from(std::env::consts::OS));
+        context.insert("arch".to_string(), Value::from(std::env::consts::ARCH));
+
+        // Add environment variables only in compatibility mode, templates use env() otherwise
+        if self.legacy_env_vars {
+            for (key, value) in self.env_policy.visible_vars() {
+                context.insert(format!("env_{}", key), Value::from(value));
+            }
         }
 
+        context.extend(self.user_vars(snippet_type));
         context
     }
+
+    /// User-defined variables of the snippet type, overridden by those from the command line
+    fn user_vars(&self, snippet_type: &str) -> HashMap<String, Value> {
+        let mut vars: HashMap<String, Value> = self
+            .type_vars
+            .iter()
+            .flat_map(|resolver| resolver.vars(snippet_type))
+            .map(|(key, value)| (key, Value::from_serialize(&value)))
+            .collect();
+        vars.extend(self.vars.clone());
+        vars
+    }
+
+    /// Names of the context variables of `snippet_type`, without reading their values
+    fn known_variables(&self, snippet_type: &str) -> HashSet<String> {
+        let mut known: HashSet<String> = CONTEXT_VARIABLES.iter().map(|name| name.to_string()).collect();
+        if self.legacy_env_vars {
+            let env_vars = self.env_policy.visible_vars();
+            known.extend(env_vars.map(|(key, _)| format!("env_{}", key)));
+        }
+        known.extend(self.user_vars(snippet_type).into_keys());
+        known.extend(read(&self.env).globals().map(|(name, _)| name.to_string()));
+        known.extend(SPECIAL_VARIABLES.iter().map(|name| name.to_string()));
+        known
+    }
 }
 
 impl TemplateEngine for MiniJinjaEngine {
-    fn render(&self, content: &SnippetContent) -> Result<String, TemplateError> {
+    fn render(&self, snippet_type: &str, content: &SnippetContent) -> Result<String, TemplateError> {
+        self.render_as(snippet_type, content, false)
+    }
+
+    /// Render without asking questions, reusing the output of shell commands run before
+    fn render_preview(&self, snippet_type: &str, content: &SnippetContent) -> Result<String, TemplateError> {
+        self.render_as(snippet_type, content, true)
+    }
+
+    fn check(&self, snippet_type: &str, content: &SnippetContent) -> Vec<TemplateIssue> {
+        let SnippetContent::Template { source } = content else {
+            return Vec::new();
+        };
+
+        let known = self.known_variables(snippet_type);
+        self.with_template(source, |template| self.template_issues(template, source, &known))
+            .unwrap_or_else(|e| {
+                let detail = e.detail().map(str::to_string).unwrap_or_else(|| e.kind().to_string());
+                vec![TemplateIssue::new(e.line(), format!("syntax error: {}", detail))]
+            })
+    }
+}
+
+impl MiniJinjaEngine {
+    fn render_as(
+        &self,
+        snippet_type: &str,
+        content: &SnippetContent,
+        preview: bool,
+    ) -> Result<String, TemplateError> {
         match content {
             SnippetContent::Static(s) => Ok(s.clone()),
-            SnippetContent::Template { source, .. } => {
-                let template = self
-                    .env
-                    .template_from_str(source)
-                    .map_err(|e| TemplateError::Syntax(e.to_string()))?;
+            SnippetContent::Template { source } => {
+                self.refresh()?;
+                let mut context = self.create_context(snippet_type);
+                if preview {
+                    context.insert(PREVIEW.to_string(), Value::from(true));
+                }
+                self.prompts.reset();
 
-                let context = self.create_context();
+                // A failed render leaves the snippets it was in on the chain
+                SNIPPET_CHAIN.with(|chain| chain.borrow_mut().clear());
+                MISSING_SNIPPET.with(|missing| missing.take());
+                self.with_template(source, |template| {
+                    template
+                        .render(RenderContext::value(context))
+                        .map_err(render_error)
+                })
+                .map_err(|e| TemplateError::Syntax(e.to_string()))?
+            }
+        }
+    }
+
+    /// Problems of the compiled `template` that would only show when rendering it
+    fn template_issues(
+        &self,
+        template: &Template,
+        source: &str,
+        known: &HashSet<String>,
+    ) -> Vec<TemplateIssue> {
+        let mut issues = Vec::new();
 
-                template
-                    .render(context)
-                    .map_err(|e| TemplateError::Rendering(e.to_string()))
+        // Undefined variables, unless guarded by `is defined` or `default`
+        let mut undefined: Vec<String> = template
+            .undeclared_variables(false)
+            .into_iter()
+            .filter(|name| !known.contains(name) && !is_guarded(source, name))
+            .collect();
+        undefined.sort();
+        for name in undefined {
+            let line = find_line(source, &format!(r"\b{}\b", regex::escape(&name)));
+            issues.push(TemplateIssue::new(line, format!("undefined variable '{}'", name)));
+        }
+
+        // Unknown filters and literal shell commands that would be rejected
+        let state = template.new_state();
+        let mut reported_filters = HashSet::new();
+        for block in BLOCK_REGEX.find_iter(source) {
+            if block.as_str().contains("endraw") {
+                continue;
+            }
+            let line = Some(line_at(source, block.start()));
+
+            let code = STRING_LITERAL_REGEX.replace_all(block.as_str(), "''");
+            for caps in FILTER_REGEX.captures_iter(&code) {
+                let Some(filter) = caps.get(1).or_else(|| caps.get(2)) else {
+                    continue;
+                };
+                let filter = filter.as_str();
+                let unknown = matches!(
+                    state.apply_filter(filter, &[]),
+                    Err(e) if e.kind() == ErrorKind::UnknownFilter
+                );
+                if unknown && reported_filters.insert(filter.to_string()) {
+                    issues.push(TemplateIssue::new(line, format!("unknown filter '{}'", filter)));
+                }
+            }
+
+            for caps in SHELL_LITERAL_REGEX.captures_iter(block.as_str()) {
+                let Some(command) = caps.get(1).or_else(|| caps.get(2)) else {
+                    continue;
+                };
+                if let Err(e) = self.shell_executor.validate(command.as_str()) {
+                    issues.push(TemplateIssue::new(
+                        line,
+                        format!("shell command '{}': {}", command.as_str(), e),
+                    ));
+                }
             }
         }
+
+        issues
     }
 }
 
+/// Whether every use of `name` may be undefined on purpose: it is tested with `is defined`,
+/// or each use outside of string literals falls back with `default`
+fn is_guarded(source: &str, name: &str) -> bool {
+    let name = regex::escape(name);
+    let tested = Regex::new(&format!(r"\b{}\s*is\s+(?:not\s+)?defined\b", name));
+    if tested.is_ok_and(|re| re.is_match(source)) {
+        return true;
+    }
+
+    let Ok(uses) = Regex::new(&format!(r"\b{}\b(\s*\|\s*(?:default|d)\b)?", name)) else {
+        return false;
+    };
+    let mut guarded = BLOCK_REGEX
+        .find_iter(source)
+        .filter(|block| !block.as_str().contains("endraw"))
+        .flat_map(|block| {
+            let code = STRING_LITERAL_REGEX.replace_all(block.as_str(), "''");
+            uses.captures_iter(&code)
+                // Attributes of other variables are not uses of `name`
+                .filter(|caps| !code[..caps.get(0).map_or(0, |m| m.start())].ends_with('.'))
+                .map(|caps| caps.get(1).is_some())
+                .collect::<Vec<_>>()
+        })
+        .peekable();
+    guarded.peek().is_some() && guarded.all(|is_guarded| is_guarded)
+}
+
+fn find_line(source: &str, pattern: &str) -> Option<usize> {
+    let re = Regex::new(pattern).ok()?;
+    re.find(source).map(|m| line_at(source, m.start()))
+}
+
+/// 1-based line of the byte offset `pos`
+fn line_at(source: &str, pos: usize) -> usize {
+    source[..pos].matches('\n').count() + 1
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// `snippet('type:name')`: render another snippet with the caller's context
+fn render_snippet(state: &State, name: &str) -> Result<Value, Error> {
+    let template = state.get_template(name)?;
+    template
+        .render(caller_context(state))
+        .map(Value::from_safe_string)
+}
+
+/// Functions bracketing each referenced snippet, see `track_snippet`
+const ENTER_SNIPPET: &str = "_rsnip_enter";
+const LEAVE_SNIPPET: &str = "_rsnip_leave";
+
+const CYCLE_DETECTED: &str = "Cycle detected";
+const SNIPPET_NOT_FOUND: &str = "Snippet not found";
+
+/// Wrap the source of the referenced snippet `name` to push it on the chain of snippets
+/// being rendered while it renders. Like MiniJinja, drop one trailing newline of the source.
+fn track_snippet(name: &str, source: &str) -> String {
+    let source = source
+        .strip_suffix('\n')
+        .map(|s| s.strip_suffix('\r').unwrap_or(s))
+        .unwrap_or(source);
+    let name = serde_json::Value::from(name).to_string();
+    format!(
+        "{{{{ {}({}) }}}}{}{{{{ {}() }}}}",
+        ENTER_SNIPPET, name, source, LEAVE_SNIPPET
+    )
+}
+
+fn enter_snippet(name: &str) -> Result<String, Error> {
+    SNIPPET_CHAIN.with(|chain| {
+        let mut chain = chain.borrow_mut();
+        if chain.iter().any(|n| n == name) {
+            return Err(Error::new(
+                ErrorKind::InvalidOperation,
+                format!("{}: {} -> {}", CYCLE_DETECTED, chain.join(" -> "), name),
+            ));
+        }
+        chain.push(name.to_string());
+        Ok(String::new())
+    })
+}
+
+fn leave_snippet() -> String {
+    SNIPPET_CHAIN.with(|chain| chain.borrow_mut().pop());
+    String::new()
+}
+
+/// `name` preceded by the snippets being rendered
+fn chain_to(name: &str) -> String {
+    SNIPPET_CHAIN.with(|chain| {
+        chain
+            .borrow()
+            .iter()
+            .map(String::as_str)
+            .chain([name])
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    })
+}
+
+/// Report broken snippet references with the chain of snippets involved
+fn render_error(e: Error) -> TemplateError {
+    let mut cause: &(dyn std::error::Error + 'static) = &e;
+    while let Some(source) = cause.source() {
+        cause = source;
+    }
+    let Some(cause) = cause.downcast_ref::<Error>() else {
+        return TemplateError::Rendering(e.to_string());
+    };
+
+    let missing = MISSING_SNIPPET.with(|missing| missing.take());
+    match (cause.kind(), cause.detail(), missing) {
+        (ErrorKind::TemplateNotFound, _, Some(chain)) => {
+            TemplateError::Reference(format!("{}: {}", SNIPPET_NOT_FOUND, chain))
+        }
+        (_, Some(detail), _) if detail.starts_with(CYCLE_DETECTED) => {
+            TemplateError::Reference(detail.to_string())
+        }
+        _ => TemplateError::Rendering(e.to_string()),
+    }
+}
+
+/// Variables visible at the call site, passed on when rendering other templates.
+/// The clipboard stays lazy, the other template reads it again if it uses it.
+fn caller_context(state: &State) -> Value {
+    RenderContext::value(
+        state
+            .known_variables()
+            .iter()
+            .filter(|key| *key != CLIPBOARD)
+            .filter_map(|key| state.lookup(key).map(|value| (key.to_string(), value))),
+    )
+}
+
+/// Render context reading the clipboard on first lookup instead of on every render,
+/// so previews, checks and editor completions do not touch it unless a template does
+#[derive(Debug)]
+struct RenderContext {
+    values: BTreeMap<String, Value>,
+    clipboard: OnceLock<Value>,
+}
+
+impl RenderContext {
+    fn value<I: IntoIterator<Item = (String, Value)>>(values: I) -> Value {
+        Value::from_object(Self {
+            values: values.into_iter().collect(),
+            clipboard: OnceLock::new(),
+        })
+    }
+}
+
+impl Object for RenderContext {
+    fn repr(self: &Arc<Self>) -> ObjectRepr {
+        ObjectRepr::Map
+    }
+
+    fn get_value(self: &Arc<Self>, key: &Value) -> Option<Value> {
+        let key = key.as_str()?;
+        match self.values.get(key) {
+            Some(value) => Some(value.clone()),
+            None if key == CLIPBOARD => Some(self.clipboard.get_or_init(read_clipboard).clone()),
+            None => None,
+        }
+    }
+
+    fn enumerate(self: &Arc<Self>) -> Enumerator {
+        let mut keys: Vec<Value> = self.values.keys().map(|key| Value::from(key.as_str())).collect();
+        if !self.values.contains_key(CLIPBOARD) {
+            keys.push(Value::from(CLIPBOARD));
+        }
+        Enumerator::Values(keys)
+    }
+}
+
+/// Clipboard text, empty if the clipboard is unavailable (e.g. headless)
+fn read_clipboard() -> Value {
+    Value::from(get_clipboard_text().unwrap_or_else(|e| {
+        debug!("Clipboard not available for template context: {}", e);
+        String::new()
+    }))
+}
+
+/// Add the template library at `path` to `env`, registering its macros and top-level variables.
+/// Returns the names registered.
+fn register_library(env: &mut Environment<'static>, path: &Path) -> Result<Vec<String>, TemplateError> {
+    let source = std::fs::read_to_string(path).map_err(|e| {
+        TemplateError::Context(format!(
+            "Failed to read template library {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let macro_names: Vec<String> = MACRO_REGEX
+        .captures_iter(&source)
+        .map(|caps| caps[1].to_string())
+        .collect();
+
+    let name = path.display().to_string();
+    env.add_template_owned(name.clone(), source)
+        .map_err(|e| library_error(path, e))?;
+
+    let exports: Vec<(String, Value)> = {
+        let template = env.get_template(&name).map_err(|e| library_error(path, e))?;
+        let captured = template
+            .render_captured(())
+            .map_err(|e| library_error(path, e))?;
+        let state = captured.state();
+        state
+            .exports()
+            .into_iter()
+            .filter_map(|export| state.lookup(export).map(|v| (export.to_string(), v)))
+            .collect()
+    };
+
+    let names = exports.iter().map(|(export, _)| export.clone()).collect();
+    for (export, value) in exports {
+        if macro_names.contains(&export) {
+            // Macros are bound to the state of their template, so each call re-evaluates
+            // the library with the caller's context and invokes the macro there
+            debug!("Registering library macro: {}", export);
+            let library = name.clone();
+            let macro_name = export.clone();
+            env.add_function(export, move |state: &State, args: Rest<Value>| {
+                let template = state.get_template(&library)?;
+                let captured = template.render_captured(caller_context(state))?;
+                captured
+                    .state()
+                    .call_macro(&macro_name, &args)
+                    .map(Value::from_safe_string)
+            });
+        } else {
+            env.add_global(export, value);
+        }
+    }
+    Ok(names)
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn library_error(path: &Path, e: Error) -> TemplateError {
+    let location = match e.line() {
+        Some(line) => format!("{}:{}", path.display(), line),
+        None => path.display().to_string(),
+    };
+    let detail = e
+        .detail()
+        .map(str::to_string)
+        .unwrap_or_else(|| e.kind().to_string());
+    TemplateError::Syntax(format!("Template library {}: {}", location, detail))
+}
+
 // Safe shell executor implementation
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct SafeShellExecutor;
 
 impl SafeShellExecutor {
@@ -106,11 +902,7 @@ impl ShellCommandExecutor for SafeShellExecutor {
     fn execute(&self, cmd: &str) -> Result<String, TemplateError> {
         info!("Executing shell command: {}", cmd);
 
-        if !self.is_command_safe(cmd) {
-            return Err(TemplateError::Shell(
-                "Command contains forbidden patterns".to_string(),
-            ));
-        }
+        self.validate(cmd)?;
 
         let output = Command::new("sh")
             .arg("-c")
@@ -128,47 +920,123 @@ impl ShellCommandExecutor for SafeShellExecutor {
     fn box_clone(&self) -> Box<dyn ShellCommandExecutor> {
         Box::new(self.clone())
     }
+
+    fn validate(&self, cmd: &str) -> Result<(), TemplateError> {
+        if !self.is_command_safe(cmd) {
+            return Err(TemplateError::Shell(
+                "Command contains forbidden patterns".to_string(),
+            ));
+        }
+        Ok(())
+    }
 }
 
-// Filter implementations
-fn date_format(value: Value, args: &[Value]) -> Result<Value, Error> {
-    let date_str = value
-        .as_str()
-        .ok_or_else(|| Error::new(ErrorKind::InvalidOperation, "Expected string date"))?;
-    let format = args.first().and_then(|v| v.as_str()).unwrap_or("%Y-%m-%d");
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let date = DateTime::parse_from_rfc3339(date_str)
-        .map_err(|e| Error::new(ErrorKind::InvalidOperation, format!("Invalid date: {}", e)))?
-        .with_timezone(&Utc);
+    fn loads(engine: &MiniJinjaEngine) -> usize {
+        lock(&engine.cache).loads
+    }
 
-    Ok(Value::from(date.format(format).to_string()))
-}
+    #[test]
+    fn given_same_source_when_rendering_twice_then_compiles_once() {
+        let engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()));
+        let content = SnippetContent::new("{{ 1 + 1 }}".to_string());
 
-fn subtract_days(value: Value, args: &[Value]) -> Result<Value, Error> {
-    let date_str = value
-        .as_str()
-        .ok_or_else(|| Error::new(ErrorKind::InvalidOperation, "Expected date string"))?;
+        assert_eq!(engine.render("default", &content).unwrap(), "2");
+        assert_eq!(engine.render("default", &content).unwrap(), "2");
+        assert!(engine.check("default", &content).is_empty());
 
-    let date = DateTime::parse_from_rfc3339(date_str)
-        .map_err(|e| Error::new(ErrorKind::InvalidOperation, format!("Invalid date: {}", e)))?;
+        assert_eq!(loads(&engine), 1);
+    }
 
-    let days = args.first().and_then(|v| v.as_i64()).unwrap_or(0);
-    let new_date = date - chrono::Duration::days(days);
+    #[test]
+    fn given_more_sources_than_cached_when_rendering_then_recompiles_only_dropped_ones() {
+        let engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()));
+        let content = |i: usize| SnippetContent::new(format!("{{{{ {} }}}}", i));
+        for i in 0..=MAX_COMPILED_TEMPLATES {
+            engine.render("default", &content(i)).unwrap();
+        }
 
-    Ok(Value::from(new_date.to_rfc3339()))
-}
+        engine.render("default", &content(MAX_COMPILED_TEMPLATES)).unwrap();
+        assert_eq!(loads(&engine), MAX_COMPILED_TEMPLATES + 1);
+        assert_eq!(engine.render("default", &content(0)).unwrap(), "0");
+        assert_eq!(loads(&engine), MAX_COMPILED_TEMPLATES + 2);
+        assert_eq!(lock(&engine.cache).snippets.len(), MAX_COMPILED_TEMPLATES);
+    }
 
-fn add_days(value: Value, args: &[Value]) -> Result<Value, Error> {
-    let date_str = value
-        .as_str()
-        .ok_or_else(|| Error::new(ErrorKind::InvalidOperation, "Expected date string"))?;
+    /// Serves `shell:a` from a source the test can edit
+    struct EditableResolver(Mutex<String>);
 
-    let date = DateTime::parse_from_rfc3339(date_str)
-        .map_err(|e| Error::new(ErrorKind::InvalidOperation, format!("Invalid date: {}", e)))?;
+    impl SnippetResolver for EditableResolver {
+        fn resolve(&self, reference: &str) -> Result<Option<String>, TemplateError> {
+            Ok((reference == "shell:a").then(|| lock(&self.0).clone()))
+        }
+    }
 
-    let days = args.first().and_then(|v| v.as_i64()).unwrap_or(0);
-    let new_date = date + chrono::Duration::days(days);
+    #[test]
+    fn given_edited_reference_when_rendering_then_recompiles_only_that_reference() {
+        let resolver = Arc::new(EditableResolver(Mutex::new("one".to_string())));
+        let engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()))
+            .with_snippet_resolver(resolver.clone());
+        let content = SnippetContent::new("{% include 'shell:a' %}".to_string());
 
-    Ok(Value::from(new_date.to_rfc3339()))
-}
+        assert_eq!(engine.render("default", &content).unwrap(), "one");
+        assert_eq!(engine.render("default", &content).unwrap(), "one");
+        assert_eq!(loads(&engine), 2);
+
+        *lock(&resolver.0) = "two".to_string();
+        assert_eq!(engine.render("default", &content).unwrap(), "two");
+        assert_eq!(loads(&engine), 3);
+    }
+
+    #[test]
+    fn given_context_when_checking_then_knows_every_builtin_variable() {
+        let engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()))
+            .with_vars([("team", serde_json::json!("ops"))]);
+        let known = engine.known_variables("default");
+
+        let unknown: Vec<String> = engine
+            .create_context("default")
+            .into_keys()
+            .filter(|name| !known.contains(name))
+            .collect();
 
+        assert!(unknown.is_empty(), "Unknown context variables: {:?}", unknown);
+    }
+
+    #[test]
+    fn given_partly_guarded_variable_when_checking_then_is_not_guarded() {
+        assert!(is_guarded("{{ name|default('x') }} {{ name | d }}", "name"));
+        assert!(is_guarded("{% if name is defined %}{{ name }}{% endif %}", "name"));
+        assert!(is_guarded("{{ user.name }} {{ name|default('x') }}", "name"));
+        assert!(!is_guarded("{{ name|default('x') }} {{ name }}", "name"));
+        assert!(!is_guarded("{{ 'name'|default('x') }}", "name"));
+    }
+
+    #[test]
+    fn given_template_without_clipboard_when_rendering_then_reads_it_only_on_use() {
+        let env = Environment::new();
+        let context = RenderContext::value([("name".to_string(), Value::from("x"))]);
+        let cell = || &context.downcast_object_ref::<RenderContext>().unwrap().clipboard;
+
+        assert_eq!(env.render_str("{{ name }}", context.clone()).unwrap(), "x");
+        assert!(cell().get().is_none());
+
+        env.render_str("{{ clipboard }}", context.clone()).unwrap();
+        assert!(cell().get().is_some());
+    }
+
+    #[test]
+    fn given_changed_source_when_rendering_then_compiles_new_template() {
+        let engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()));
+
+        let first = engine.render("default", &SnippetContent::new("{{ 'a' }}".to_string()));
+        let second = engine.render("default", &SnippetContent::new("{{ 'b' }}".to_string()));
+
+        assert_eq!(first.unwrap(), "a");
+        assert_eq!(second.unwrap(), "b");
+        assert_eq!(loads(&engine), 2);
+    }
+}
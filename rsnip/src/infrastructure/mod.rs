@@ -1,6 +1,15 @@
 pub mod parsers;
 pub mod clipboard;
+pub mod dates;
 pub mod edit_snippets;
+pub mod env_vars;
+pub mod filters;
+pub mod highlight;
 pub mod minijinja;
+pub mod prompts;
 pub mod complete;
-pub mod fuzzy;
\ No newline at end of file
+pub mod fuzzy;
+pub mod git;
+pub mod menu;
+pub mod tmux;
+pub mod lsp;
\ No newline at end of file
@@ -1,9 +1,10 @@
 use crate::domain::content::SnippetContent;
 use crate::domain::errors::{SnippetError, SnippetResult};
+use crate::domain::output::{parse_output, OutputTransform};
 use crate::domain::parser::SnippetParser;
 use crate::domain::snippet::Snippet;
 use std::path::Path;
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, warn};
 
 pub struct DefaultSnippetParser;
 
@@ -36,11 +37,21 @@ impl DefaultSnippetParser {
     }
 }
 
+/// A language is a single word such as `bash`, `c++` or `objective-c`
+fn is_language(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+' | '#' | '.'))
+}
+
 #[derive(Clone, Debug)]
 struct SnippetBuilder {
     name: Option<String>,
     content_lines: Vec<String>,
     comments: Vec<String>,
+    language: Option<String>,
+    output: Option<Vec<OutputTransform>>,
     start_line: usize,
 }
 
@@ -50,6 +61,8 @@ impl SnippetBuilder {
             name: None,
             content_lines: Vec::new(),
             comments: Vec::new(),
+            language: None,
+            output: None,
             start_line: 0,
         }
     }
@@ -68,6 +81,8 @@ impl SnippetBuilder {
                     name,
                     content: SnippetContent::new(snippet_text),
                     comments: self.comments,
+                    language: self.language,
+                    output: self.output,
                 }))
             }
             None => Ok(None),
@@ -130,8 +145,34 @@ impl SnippetParser for DefaultSnippetParser {
                 last_line_empty = false;
             } else if in_snippet {
                 // Handle content or comment
-                if trimmed.starts_with(':') {
-                    builder.comments.push(trimmed[1..].trim().to_string());
+                if let Some(comment) = trimmed.strip_prefix(':') {
+                    // `: language: rust` and `: output: dedent` are metadata, not comments,
+                    // but only in the header above the first content line
+                    let comment = comment.trim();
+                    let in_header = builder.content_lines.is_empty();
+                    if let Some(language) = comment
+                        .strip_prefix("language:")
+                        .map(str::trim)
+                        .filter(|language| in_header && is_language(language))
+                    {
+                        builder.language = Some(language.to_string());
+                    } else if let Some(output) = comment
+                        .strip_prefix("output:")
+                        .filter(|_| in_header)
+                        .and_then(|output| match parse_output(output) {
+                            Ok(output) => Some(output),
+                            // Free text like `: output: prints the diff` stays a comment,
+                            // `rsnip check` reports it
+                            Err(reason) => {
+                                warn!("{}:{}: {}, keeping it as a comment", path.display(), line_num, reason);
+                                None
+                            }
+                        })
+                    {
+                        builder.output = Some(output);
+                    } else {
+                        builder.comments.push(comment.to_string());
+                    }
                 } else {
                     // Only add empty lines if they're not at the start/end and not consecutive
                     if !trimmed.is_empty()
@@ -107,6 +107,8 @@ impl SnippetParser for SclsSnippetParser {
                 name: scls_snippet.prefix,
                 content: SnippetContent::new(body_with_placeholders),
                 comments,
+                language: scls_snippet.scope.first().cloned(),
+                output: None,
             }
         }).collect();
 
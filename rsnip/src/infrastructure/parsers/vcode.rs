@@ -14,6 +14,9 @@ struct VCodeSnippet {
     body: SnippetBody,
     #[serde(default)]
     description: Option<String>,
+    /// Comma-separated language ids, e.g. "javascript,typescript"
+    #[serde(default)]
+    scope: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -74,8 +77,8 @@ impl SnippetParser for VCodeSnippetParser {
             })?;
 
         let result = snippets
-            .into_iter()
-            .map(|(_, snippet)| {
+            .into_values()
+            .map(|snippet| {
                 let body = match snippet.body {
                     SnippetBody::Single(text) => text,
                     SnippetBody::Multiple(lines) => lines.join("\n"),
@@ -83,10 +86,18 @@ impl SnippetParser for VCodeSnippetParser {
 
                 let comments = snippet.description.map_or_else(Vec::new, |desc| vec![desc]);
 
+                let language = snippet
+                    .scope
+                    .as_deref()
+                    .and_then(|scope| scope.split(',').map(str::trim).find(|s| !s.is_empty()))
+                    .map(str::to_string);
+
                 Snippet {
                     name: snippet.prefix,
                     content: SnippetContent::new(body),
                     comments,
+                    language,
+                    output: None,
                 }
             })
             .collect();
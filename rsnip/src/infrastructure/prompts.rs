@@ -0,0 +1,230 @@
+// infrastructure/prompts.rs
+use crate::domain::template::{errors::TemplateError, interface::UserPrompter};
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Confirm, Input, Select};
+use minijinja::value::Kwargs;
+use minijinja::{Environment, Error, ErrorKind, State, Value};
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+/// Context variable set while rendering a preview, which must not ask
+pub const PREVIEW: &str = "_rsnip_preview";
+
+/// Whether the template is rendered as a preview
+pub fn is_preview(state: &State) -> bool {
+    state.lookup(PREVIEW).is_some_and(|value| value.is_true())
+}
+
+/// Asks on the terminal via stderr so rendered output on stdout stays clean
+#[derive(Clone, Debug, Default)]
+pub struct TerminalPrompter;
+
+impl UserPrompter for TerminalPrompter {
+    fn is_interactive(&self) -> bool {
+        std::io::stdin().is_terminal() && std::io::stderr().is_terminal()
+    }
+
+    fn input(&self, label: &str, default: Option<&str>) -> Result<String, TemplateError> {
+        let theme = ColorfulTheme::default();
+        let mut input = Input::<String>::with_theme(&theme)
+            .with_prompt(label)
+            .allow_empty(true);
+        if let Some(default) = default {
+            input = input.default(default.to_string());
+        }
+        input
+            .interact_text()
+            .map_err(|e| TemplateError::Prompt(e.to_string()))
+    }
+
+    fn confirm(&self, label: &str, default: bool) -> Result<bool, TemplateError> {
+        Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(label)
+            .default(default)
+            .interact()
+            .map_err(|e| TemplateError::Prompt(e.to_string()))
+    }
+
+    fn select(&self, label: &str, options: &[String], default: usize) -> Result<usize, TemplateError> {
+        Select::with_theme(&ColorfulTheme::default())
+            .with_prompt(label)
+            .items(options)
+            .default(default)
+            .interact()
+            .map_err(|e| TemplateError::Prompt(e.to_string()))
+    }
+}
+
+/// Answers for one render: preset values from the command line take precedence,
+/// everything asked is cached so repeated questions are only asked once.
+pub struct PromptSession {
+    prompter: Arc<dyn UserPrompter>,
+    preset: HashMap<String, String>,
+    answers: Mutex<HashMap<String, Value>>,
+}
+
+impl PromptSession {
+    pub fn new(prompter: Arc<dyn UserPrompter>, preset: HashMap<String, String>) -> Self {
+        Self {
+            prompter,
+            preset,
+            answers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Forget answers of the previous render
+    pub fn reset(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Value>> {
+        self.answers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Answer from cache or command line, otherwise ask via `ask`.
+    /// Previews never ask and use `placeholder` instead.
+    fn answer(
+        &self,
+        state: &State,
+        function: &str,
+        label: &str,
+        from_preset: impl FnOnce(&str) -> Result<Value, Error>,
+        placeholder: impl FnOnce() -> Value,
+        ask: impl FnOnce(&dyn UserPrompter) -> Result<Value, TemplateError>,
+    ) -> Result<Value, Error> {
+        let key = format!("{}:{}", function, label);
+        if let Some(value) = self.lock().get(&key) {
+            return Ok(value.clone());
+        }
+
+        let value = match self.preset.get(label) {
+            Some(preset) => from_preset(preset)?,
+            None if is_preview(state) => placeholder(),
+            None if self.prompter.is_interactive() => {
+                debug!("Asking {}('{}')", function, label);
+                ask(self.prompter.as_ref())
+                    .map_err(|e| Error::new(ErrorKind::InvalidOperation, e.to_string()))?
+            }
+            None => {
+                return Err(Error::new(
+                    ErrorKind::InvalidOperation,
+                    format!(
+                        "{}('{}') needs an answer but no terminal is available, pass --answer '{}=VALUE'",
+                        function, label, label
+                    ),
+                ))
+            }
+        };
+
+        self.lock().insert(key, value.clone());
+        Ok(value)
+    }
+}
+
+/// Register `prompt()`, `confirm()` and `choose()` answering through `session`
+pub fn register(env: &mut Environment<'static>, session: Arc<PromptSession>) {
+    let prompt_session = session.clone();
+    env.add_function(
+        "prompt",
+        move |state: &State, label: &str, default: Option<String>, kwargs: Kwargs| {
+            let default = match default {
+                Some(default) => Some(default),
+                None => kwargs.get::<Option<String>>("default")?,
+            };
+            kwargs.assert_all_used()?;
+            prompt_session.answer(
+                state,
+                "prompt",
+                label,
+                |preset| Ok(Value::from(preset)),
+                || Value::from(default.clone().unwrap_or_else(|| format!("<{}>", label))),
+                |prompter| prompter.input(label, default.as_deref()).map(Value::from),
+            )
+        },
+    );
+
+    let confirm_session = session.clone();
+    env.add_function(
+        "confirm",
+        move |state: &State, label: &str, default: Option<bool>, kwargs: Kwargs| {
+            let default = match default {
+                Some(default) => default,
+                None => kwargs.get::<Option<bool>>("default")?.unwrap_or(false),
+            };
+            kwargs.assert_all_used()?;
+            confirm_session.answer(
+                state,
+                "confirm",
+                label,
+                |preset| parse_bool(label, preset).map(Value::from),
+                || Value::from(default),
+                |prompter| prompter.confirm(label, default).map(Value::from),
+            )
+        },
+    );
+
+    env.add_function(
+        "choose",
+        move |state: &State, label: &str, options: Vec<Value>, default: Option<Value>, kwargs: Kwargs| {
+            let default = match default {
+                Some(default) => Some(default),
+                None => kwargs.get::<Option<Value>>("default")?,
+            };
+            kwargs.assert_all_used()?;
+            if options.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidOperation,
+                    format!("choose('{}') needs at least one option", label),
+                ));
+            }
+
+            let names: Vec<String> = options.iter().map(|o| o.to_string()).collect();
+            let default_index = default
+                .and_then(|d| names.iter().position(|n| *n == d.to_string()))
+                .unwrap_or(0);
+            session.answer(
+                state,
+                "choose",
+                label,
+                |preset| {
+                    names
+                        .iter()
+                        .position(|n| n == preset)
+                        .map(|i| options[i].clone())
+                        .ok_or_else(|| {
+                            Error::new(
+                                ErrorKind::InvalidOperation,
+                                format!(
+                                    "Answer '{}' for choose('{}') is not one of: {}",
+                                    preset,
+                                    label,
+                                    names.join(", ")
+                                ),
+                            )
+                        })
+                },
+                || options[default_index].clone(),
+                |prompter| {
+                    prompter
+                        .select(label, &names, default_index)
+                        .map(|i| options[i].clone())
+                },
+            )
+        },
+    );
+}
+
+fn parse_bool(label: &str, value: &str) -> Result<bool, Error> {
+    match value.trim().to_lowercase().as_str() {
+        "y" | "yes" | "true" | "1" => Ok(true),
+        "n" | "no" | "false" | "0" => Ok(false),
+        _ => Err(Error::new(
+            ErrorKind::InvalidOperation,
+            format!("Answer '{}' for confirm('{}') is not yes or no", value, label),
+        )),
+    }
+}
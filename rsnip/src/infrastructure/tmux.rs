@@ -0,0 +1,84 @@
+// infrastructure/tmux.rs
+use anyhow::{anyhow, Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use tracing::{debug, instrument};
+
+/// Client for the tmux server of the current session, or of a named socket
+#[derive(Clone, Debug, Default)]
+pub struct Tmux {
+    socket: Option<String>,
+}
+
+impl Tmux {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Talk to the server listening on socket `name` (`tmux -L name`)
+    pub fn with_socket(mut self, name: impl Into<String>) -> Self {
+        self.socket = Some(name.into());
+        self
+    }
+
+    /// Load `text` into the paste buffer `name`, a new buffer on top of the stack if `None`
+    #[instrument(level = "debug", skip(self, text))]
+    pub fn load_buffer(&self, text: &str, name: Option<&str>) -> Result<()> {
+        let mut args = vec!["load-buffer"];
+        if let Some(name) = name {
+            args.extend(["-b", name]);
+        }
+        args.push("-");
+        self.run(&args, Some(text)).map(|_| ())
+    }
+
+    /// Paste `text` into `pane`, the active pane if `None`, without pressing enter.
+    /// Uses bracketed paste where the pane's program supports it, so lines are not run.
+    #[instrument(level = "debug", skip(self, text))]
+    pub fn send_to_pane(&self, text: &str, pane: Option<&str>) -> Result<()> {
+        let buffer = format!("rsnip-{}", std::process::id());
+        self.load_buffer(text, Some(&buffer))?;
+
+        let mut args = vec!["paste-buffer", "-p", "-d", "-b", &buffer];
+        if let Some(pane) = pane {
+            args.extend(["-t", pane]);
+        }
+        if let Err(e) = self.run(&args, None) {
+            // `-d` only deletes the buffer once it is pasted
+            let _ = self.run(&["delete-buffer", "-b", &buffer], None);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Run a tmux command, feeding `stdin` to it, and return its output
+    fn run(&self, args: &[&str], stdin: Option<&str>) -> Result<String> {
+        let mut command = Command::new("tmux");
+        if let Some(socket) = &self.socket {
+            command.args(["-L", socket]);
+        }
+        command
+            .args(args)
+            .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        debug!("Running {:?}", command);
+
+        let mut child = command.spawn().context("Failed to run tmux")?;
+        if let (Some(text), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            pipe.write_all(text.as_bytes())
+                .context("Failed to write to tmux")?;
+        }
+        let output = child.wait_with_output().context("Failed to run tmux")?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            Err(anyhow!(
+                "tmux {} failed: {}",
+                args[0],
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+}
@@ -2,7 +2,7 @@ use clap::Parser;
 use crossterm::style::Stylize;
 use rsnip::cli::args::Cli;
 use rsnip::cli::commands::execute_command;
-use rsnip::infrastructure::complete::generate_completion_script;
+use rsnip::infrastructure::complete::{generate_completion_script, generate_tmux_bindings};
 use rsnip::config::{Settings, SnippetTypeConfig};
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::filter::filter_fn;
@@ -32,7 +32,10 @@ fn main() {
     };
 
     if let Some(shell) = cli.generator {
-        if let Err(e) = generate_completion_script(shell, std::io::stdout(), &config) {
+        use clap::CommandFactory;
+        if let Err(e) =
+            generate_completion_script(shell, &Cli::command(), std::io::stdout(), &config)
+        {
             eprintln!(
                 "{}",
                 format!("Error generating completion script: {}", e).red()
@@ -42,6 +45,14 @@ fn main() {
         return;
     }
 
+    if cli.generate_tmux {
+        if let Err(e) = generate_tmux_bindings(std::io::stdout(), &config) {
+            eprintln!("{}", format!("Error generating tmux key bindings: {}", e).red());
+            std::process::exit(1);
+        }
+        return;
+    }
+
     if cli.info {
         use clap::CommandFactory; // Trait which returns the current command
         if let Some(a) = Cli::command().get_author() {
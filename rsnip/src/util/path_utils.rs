@@ -13,8 +13,8 @@ pub fn expand_path<P: AsRef<Path>  + std::fmt::Debug>(path: P) -> Result<PathBuf
 
         if path_str == "~" {
             Ok(home)
-        } else if path_str.starts_with("~/") {
-            Ok(home.join(&path_str[2..]))
+        } else if let Some(rest) = path_str.strip_prefix("~/") {
+            Ok(home.join(rest))
         } else {
             Err(anyhow::anyhow!("Invalid path: {}", path_str))
         }
@@ -2,7 +2,7 @@
 
 use rsnip::application::services::CompletionService;
 use rsnip::application::snippet_service::SnippetService;
-use rsnip::config::{Settings, SnippetTypeConfig};
+use rsnip::config::{AliasConfig, Settings, SnippetTypeConfig};
 use rsnip::domain::content::SnippetContent;
 use rsnip::domain::snippet::Snippet;
 use rsnip::infrastructure::minijinja::{MiniJinjaEngine, SafeShellExecutor};
@@ -16,11 +16,15 @@ fn create_test_snippets() -> Vec<Snippet> {
             name: "apple".to_string(),
             content: SnippetContent::Static("apple content".to_string()),
             comments: vec![],
+            language: None,
+            output: None,
         },
         Snippet {
             name: "banana".to_string(),
             content: SnippetContent::Static("banana content".to_string()),
             comments: vec![],
+            language: None,
+            output: None,
         },
     ]
 }
@@ -34,13 +38,16 @@ fn create_test_settings_single(source_file: std::path::PathBuf) -> Settings {
             description: None,
             alias: None,
             format: "default".to_string(),
+            vars: HashMap::new(),
+            output: None,
+            aliases: AliasConfig::default(),
         },
     );
 
     Settings {
         snippet_types,
         config_paths: vec![],
-        active_config_path: None,
+        ..Default::default()
     }
 }
 
@@ -0,0 +1,120 @@
+// application/services/tests/editor_tests.rs
+use anyhow::Result;
+use rsnip::application::services::EditorService;
+use rsnip::config::{AliasConfig, Settings, SnippetTypeConfig};
+use rsnip::domain::content::SnippetContent;
+use rsnip::domain::editor::SnippetProvider;
+use rsnip::domain::template::errors::TemplateError;
+use rsnip::domain::template::interface::TemplateEngine;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+struct MockTemplateEngine;
+
+impl TemplateEngine for MockTemplateEngine {
+    fn render(&self, _snippet_type: &str, content: &SnippetContent) -> Result<String, TemplateError> {
+        Ok(content.get_content().replace("{{ user }}", "$alice"))
+    }
+}
+
+fn concrete(path: &Path, format: &str) -> SnippetTypeConfig {
+    SnippetTypeConfig::Concrete {
+        source_file: path.to_path_buf(),
+        description: None,
+        alias: None,
+        format: format.to_string(),
+        vars: HashMap::new(),
+        output: None,
+        aliases: AliasConfig::default(),
+    }
+}
+
+fn settings(dir: &TempDir) -> Result<Settings> {
+    let shell_file = dir.path().join("shell.sh");
+    fs::write(
+        &shell_file,
+        "--- gc\n: Commit all\ngit commit -m \"$0\" --all\n---\n\
+         --- home\necho $HOME\n---\n\
+         --- who\necho {{ user }}\n---\n\
+         --- venv\n: language: python\nimport venv\n---\n",
+    )?;
+    let vscode_file = dir.path().join("rust.json");
+    fs::write(
+        &vscode_file,
+        r#"{ "main": { "prefix": "main", "body": ["fn main() {", "    ${1:todo!()}", "}"], "scope": "rust" } }"#,
+    )?;
+
+    let mut snippet_types = HashMap::new();
+    snippet_types.insert("shell".to_string(), concrete(&shell_file, "default"));
+    snippet_types.insert("rust".to_string(), concrete(&vscode_file, "vcode"));
+    Ok(Settings {
+        snippet_types,
+        config_paths: vec![],
+        ..Default::default()
+    })
+}
+
+#[test]
+fn given_document_language_when_listing_then_offers_matching_and_unscoped_snippets() -> Result<()> {
+    // Arrange
+    let dir = TempDir::new()?;
+    let settings = settings(&dir)?;
+    let service = EditorService::new(&settings, Box::new(|_| Ok(Box::new(MockTemplateEngine))));
+
+    // Act
+    let snippets = service.snippets(Some("shellscript"))?;
+
+    // Assert
+    let names: Vec<&str> = snippets.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["gc", "home", "who"]);
+    assert_eq!(snippets[0].description.as_deref(), Some("Commit all"));
+    assert_eq!(
+        snippets[0].insert_text.as_deref(),
+        Some(r#"git commit -m "$0" --all"#)
+    );
+    assert_eq!(snippets[1].insert_text.as_deref(), Some(r"echo \$HOME"));
+    assert_eq!(snippets[2].insert_text, None);
+    assert_eq!(service.snippets(None)?.len(), 5);
+    Ok(())
+}
+
+#[test]
+fn given_vscode_snippet_when_listing_then_keeps_its_tabstops() -> Result<()> {
+    // Arrange
+    let dir = TempDir::new()?;
+    let settings = settings(&dir)?;
+    let service = EditorService::new(&settings, Box::new(|_| Ok(Box::new(MockTemplateEngine))))
+        .with_snippet_type("rust");
+
+    // Act
+    let snippets = service.snippets(Some("rust"))?;
+
+    // Assert
+    assert_eq!(snippets.len(), 1);
+    assert_eq!(
+        snippets[0].insert_text.as_deref(),
+        Some("fn main() {\n    ${1:todo!()}\n}")
+    );
+    Ok(())
+}
+
+#[test]
+fn given_template_when_resolving_then_renders_with_engine_of_its_type() -> Result<()> {
+    // Arrange
+    let dir = TempDir::new()?;
+    let settings = settings(&dir)?;
+    let service = EditorService::new(&settings, Box::new(|ctype| {
+        assert_eq!(ctype, "shell");
+        Ok(Box::new(MockTemplateEngine))
+    }));
+
+    // Act
+    let text = service.resolve("shell", "who")?;
+
+    // Assert
+    assert_eq!(text, r"echo \$alice");
+    assert!(service.resolve("shell", "missing").is_err());
+    Ok(())
+}
@@ -4,10 +4,12 @@ use std::collections::HashMap;
 use std::io::Write;
 use std::path::PathBuf;
 use tempfile::NamedTempFile;
-use rsnip::application::services::SnippetManagementService;
+use rsnip::application::services::{SnippetLibrary, SnippetManagementService};
 use rsnip::application::snippet_service::SnippetService;
-use rsnip::config::{Settings, SnippetTypeConfig};
+use rsnip::config::{AliasConfig, Settings, SnippetTypeConfig};
+use rsnip::domain::template::interface::{SnippetResolver, TemplateEngine};
 use rsnip::infrastructure::minijinja::{MiniJinjaEngine, SafeShellExecutor};
+use std::sync::Arc;
 
 fn create_test_settings(files: &[(String, PathBuf)]) -> Settings {
     let mut snippet_types = HashMap::new();
@@ -21,6 +23,9 @@ fn create_test_settings(files: &[(String, PathBuf)]) -> Settings {
                 description: None,
                 alias: None,
                 format: "default".to_string(),
+                vars: HashMap::new(),
+                output: None,
+                aliases: AliasConfig::default(),
             },
         );
     }
@@ -28,7 +33,7 @@ fn create_test_settings(files: &[(String, PathBuf)]) -> Settings {
     Settings {
         snippet_types,
         config_paths: vec![],
-        active_config_path: None,
+        ..Default::default()
     }
 }
 
@@ -63,6 +68,9 @@ fn create_test_settings_combined(source_files: Vec<std::path::PathBuf>) -> Setti
                 description: None,
                 alias: None,
                 format: "default".to_string(),
+                vars: HashMap::new(),
+                output: None,
+                aliases: AliasConfig::default(),
             },
         );
     }
@@ -78,13 +86,16 @@ fn create_test_settings_combined(source_files: Vec<std::path::PathBuf>) -> Setti
             sources,
             description: None,
             alias: None,
+            vars: HashMap::new(),
+            output: None,
+            aliases: AliasConfig::default(),
         },
     );
 
     Settings {
         snippet_types,
         config_paths: vec![],
-        active_config_path: None,
+        ..Default::default()
     }
 }
 
@@ -113,4 +124,72 @@ fn given_combined_type_when_getting_snippets_then_returns_all_snippets() -> Resu
     assert!(snippets.iter().any(|s| s.name == "test1"));
     assert!(snippets.iter().any(|s| s.name == "test2"));
     Ok(())
-}
\ No newline at end of file
+}
+#[test]
+fn given_snippet_library_when_resolving_references_then_finds_qualified_and_default_type() -> Result<()> {
+    // Arrange
+    let mut temp_file1 = NamedTempFile::new()?;
+    let mut temp_file2 = NamedTempFile::new()?;
+    writeln!(temp_file1, "--- header\n# Header {{{{ year }}}}\n---")?;
+    writeln!(temp_file2, "--- footer\n# Footer\n---")?;
+    let settings = create_test_settings(&[
+        ("shell".to_string(), temp_file1.path().to_path_buf()),
+        ("other".to_string(), temp_file2.path().to_path_buf()),
+    ]);
+    let library = SnippetLibrary::new(settings, "shell");
+
+    // Act & Assert
+    assert_eq!(library.resolve("header")?.as_deref(), Some("# Header {{ year }}"));
+    assert_eq!(library.resolve("other:footer")?.as_deref(), Some("# Footer"));
+    assert_eq!(library.resolve("other:header")?, None);
+    assert!(library.resolve("unknown:header").is_err());
+    Ok(())
+}
+
+#[test]
+fn given_template_including_snippet_when_copying_then_renders_composition() -> Result<()> {
+    // Arrange
+    let mut temp_file = NamedTempFile::new()?;
+    writeln!(
+        temp_file,
+        "--- header\n# (c) ACME\n---\n--- script\n{{% include 'test:header' %}}\necho hi\n---"
+    )?;
+    let settings = create_test_settings(&[("test".to_string(), temp_file.path().to_path_buf())]);
+    let library = SnippetLibrary::new(settings.clone(), "test");
+    let engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()))
+        .with_snippet_resolver(Arc::new(library));
+    let service = SnippetManagementService::new(&settings);
+
+    // Act
+    let snippets = service.get_snippets("test")?;
+    let script = snippets.iter().find(|s| s.name == "script").unwrap();
+    let rendered = engine.render("default", &script.content)?;
+
+    // Assert
+    assert_eq!(rendered, "# (c) ACME\necho hi");
+    Ok(())
+}
+
+#[test]
+fn given_edited_snippet_file_when_rendering_again_then_includes_new_source() -> Result<()> {
+    // Arrange: a long-running engine, like the language server's
+    let mut temp_file = NamedTempFile::new()?;
+    write!(temp_file, "--- header\n# v1\n---\n")?;
+    let settings = create_test_settings(&[("test".to_string(), temp_file.path().to_path_buf())]);
+    let library = SnippetLibrary::new(settings, "test");
+    let engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()))
+        .with_snippet_resolver(Arc::new(library));
+    let content = rsnip::domain::content::SnippetContent::new("{% include 'header' %}".to_string());
+    let first = engine.render("test", &content)?;
+
+    // Act
+    std::fs::write(temp_file.path(), "--- header\n# v2\n---\n")?;
+    let modified = std::time::SystemTime::now() + std::time::Duration::from_secs(10);
+    temp_file.as_file().set_modified(modified)?;
+    let second = engine.render("test", &content)?;
+
+    // Assert
+    assert_eq!(first, "# v1");
+    assert_eq!(second, "# v2");
+    Ok(())
+}
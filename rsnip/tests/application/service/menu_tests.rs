@@ -0,0 +1,52 @@
+// application/services/tests/menu_tests.rs
+use anyhow::Result;
+use rsnip::application::services::MenuService;
+use rsnip::config::{AliasConfig, Settings, SnippetTypeConfig};
+use rsnip::domain::menu::MenuEntry;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+fn concrete(path: &Path) -> SnippetTypeConfig {
+    SnippetTypeConfig::Concrete {
+        source_file: path.to_path_buf(),
+        description: None,
+        alias: None,
+        format: "default".to_string(),
+        vars: HashMap::new(),
+        output: None,
+        aliases: AliasConfig::default(),
+    }
+}
+
+#[test]
+fn given_all_types_when_listing_menu_entries_then_skips_missing_files_and_sorts() -> Result<()> {
+    // Arrange
+    let mut shell_file = NamedTempFile::new()?;
+    write!(shell_file, "--- ll\nls -l\n---\n--- gc\n: Commit all\ngit commit\n---\n")?;
+    let mut notes_file = NamedTempFile::new()?;
+    write!(notes_file, "--- todo\nTODO\n---\n")?;
+
+    let mut snippet_types = HashMap::new();
+    snippet_types.insert("shell".to_string(), concrete(shell_file.path()));
+    snippet_types.insert("notes".to_string(), concrete(notes_file.path()));
+    snippet_types.insert("gone".to_string(), concrete(&PathBuf::from("/nonexistent/snippets.txt")));
+    let settings = Settings {
+        snippet_types,
+        config_paths: vec![],
+        ..Default::default()
+    };
+    let service = MenuService::new(&settings);
+
+    // Act
+    let entries = service.entries(None)?;
+
+    // Assert
+    let labels: Vec<String> = entries.iter().map(MenuEntry::label).collect();
+    assert_eq!(labels, vec!["notes/todo", "shell/gc", "shell/ll"]);
+    assert_eq!(entries[1].display(), "shell/gc  Commit all");
+    assert_eq!(service.entries(Some("notes"))?.len(), 1);
+    assert!(service.entries(Some("gone")).is_err());
+    Ok(())
+}
@@ -1,4 +1,8 @@
 // application/services/tests/mod.rs
 mod management_tests;
 mod completion_tests;
-mod template_tests;
\ No newline at end of file
+mod template_tests;
+mod validation_tests;
+mod scaffold_tests;
+mod menu_tests;
+mod editor_tests;
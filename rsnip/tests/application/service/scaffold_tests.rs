@@ -0,0 +1,84 @@
+// application/services/tests/scaffold_tests.rs
+use anyhow::Result;
+use rsnip::application::services::ScaffoldPlan;
+use rsnip::domain::manifest::ManifestEntry;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+fn create_plan(dest: &TempDir) -> ScaffoldPlan {
+    ScaffoldPlan::new(
+        dest.path(),
+        vec![
+            ManifestEntry {
+                path: PathBuf::from("src/parser.rs"),
+                content: Some("fn parse() {}\n".to_string()),
+            },
+            ManifestEntry {
+                path: PathBuf::from("logs"),
+                content: None,
+            },
+            ManifestEntry {
+                path: PathBuf::from("Cargo.toml"),
+                content: Some("[package]\n".to_string()),
+            },
+        ],
+    )
+}
+
+#[test]
+fn given_empty_destination_when_writing_then_creates_files_and_directories() -> Result<()> {
+    // Arrange
+    let dest = TempDir::new()?;
+    let plan = create_plan(&dest);
+
+    // Act
+    plan.write(false)?;
+
+    // Assert
+    assert_eq!(fs::read_to_string(dest.path().join("src/parser.rs"))?, "fn parse() {}\n");
+    assert_eq!(fs::read_to_string(dest.path().join("Cargo.toml"))?, "[package]\n");
+    assert!(dest.path().join("logs").is_dir());
+    Ok(())
+}
+
+#[test]
+fn given_existing_file_when_writing_without_force_then_refuses_and_keeps_file() -> Result<()> {
+    // Arrange
+    let dest = TempDir::new()?;
+    fs::write(dest.path().join("Cargo.toml"), "original")?;
+    let plan = create_plan(&dest);
+
+    // Act
+    let result = plan.write(false);
+
+    // Assert
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("--force"));
+    assert!(err.contains("Cargo.toml"));
+    assert_eq!(fs::read_to_string(dest.path().join("Cargo.toml"))?, "original");
+    assert!(!dest.path().join("src").exists());
+
+    plan.write(true)?;
+    assert_eq!(fs::read_to_string(dest.path().join("Cargo.toml"))?, "[package]\n");
+    Ok(())
+}
+
+#[test]
+fn given_plan_when_rendering_tree_then_shows_nested_paths_and_existing_files() -> Result<()> {
+    // Arrange
+    let dest = TempDir::new()?;
+    fs::write(dest.path().join("Cargo.toml"), "original")?;
+    let plan = create_plan(&dest);
+
+    // Act
+    let tree = plan.tree();
+
+    // Assert
+    let expected = format!(
+        "{}\n├── Cargo.toml  (exists)\n├── logs/\n└── src/\n    └── parser.rs\n",
+        dest.path().display()
+    );
+    assert_eq!(tree, expected);
+    Ok(())
+}
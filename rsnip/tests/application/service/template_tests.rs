@@ -2,6 +2,7 @@
 use anyhow::Result;
 use rsnip::application::services::TemplateProcessingService;
 use rsnip::domain::content::SnippetContent;
+use rsnip::domain::output::{OutputTransform, DEFAULT_OUTPUT};
 use rsnip::domain::snippet::Snippet;
 use rsnip::domain::template::errors::TemplateError;
 use rsnip::domain::template::interface::TemplateEngine;
@@ -9,7 +10,7 @@ use rsnip::domain::template::interface::TemplateEngine;
 struct MockTemplateEngine;
 
 impl TemplateEngine for MockTemplateEngine {
-    fn render(&self, content: &SnippetContent) -> Result<String, TemplateError> {
+    fn render(&self, _snippet_type: &str, content: &SnippetContent) -> Result<String, TemplateError> {
         match content {
             SnippetContent::Static(s) => Ok(s.clone()),
             SnippetContent::Template { source, .. } => Ok(format!("Rendered: {}", source)),
@@ -25,12 +26,95 @@ fn given_static_content_when_processing_then_copies_unchanged() -> Result<()> {
         name: "test".to_string(),
         content: SnippetContent::Static("static content".to_string()),
         comments: vec![],
+        language: None,
+        output: None,
     };
 
     // Act
-    let result = service.process_and_copy(&snippet)?;
+    let result = service.process_and_copy("default", &snippet, DEFAULT_OUTPUT)?;
 
     // Assert
     assert_eq!(result, "static content");
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn given_type_output_when_rendering_then_applies_pipeline() -> Result<()> {
+    // Arrange
+    let service = TemplateProcessingService::new(Box::new(MockTemplateEngine));
+    let snippet = Snippet {
+        name: "test".to_string(),
+        content: SnippetContent::Static("echo \\\n  hello\n".to_string()),
+        comments: vec![],
+        language: None,
+        output: None,
+    };
+
+    // Act
+    let result = service.render_output("default", &snippet, &[OutputTransform::SingleLine])?;
+
+    // Assert
+    assert_eq!(result, "echo hello");
+    Ok(())
+}
+
+#[test]
+fn given_snippet_output_when_rendering_then_overrides_type_output() -> Result<()> {
+    // Arrange
+    let service = TemplateProcessingService::new(Box::new(MockTemplateEngine));
+    let snippet = Snippet {
+        name: "test".to_string(),
+        content: SnippetContent::Static("it's\n".to_string()),
+        comments: vec![],
+        language: None,
+        output: Some(vec![OutputTransform::Trim, OutputTransform::ShellQuote]),
+    };
+
+    // Act
+    let result = service.render_output("default", &snippet, &[OutputTransform::JsonEscape])?;
+
+    // Assert
+    assert_eq!(result, "'it'\\''s'");
+    Ok(())
+}
+
+#[test]
+fn given_cursor_marker_when_expanding_then_strips_marker_and_keeps_offset() -> Result<()> {
+    // Arrange
+    let service = TemplateProcessingService::new(Box::new(MockTemplateEngine));
+    let snippet = Snippet {
+        name: "test".to_string(),
+        content: SnippetContent::Static("  git commit -m \"$0\"\n".to_string()),
+        comments: vec![],
+        language: None,
+        output: Some(vec![OutputTransform::Dedent, OutputTransform::Trim]),
+    };
+
+    // Act
+    let result = service.render_expansion("default", &snippet, DEFAULT_OUTPUT)?;
+
+    // Assert
+    assert_eq!(result.text, "git commit -m \"\"");
+    assert_eq!(result.cursor, 15);
+    Ok(())
+}
+
+#[test]
+fn given_cursor_marker_when_rendering_for_copy_then_keeps_text_unchanged() -> Result<()> {
+    // Arrange
+    let service = TemplateProcessingService::new(Box::new(MockTemplateEngine));
+    let snippet = Snippet {
+        name: "test".to_string(),
+        content: SnippetContent::Static("awk '{print $0}' \u{E000}\n".to_string()),
+        comments: vec![],
+        language: None,
+        output: None,
+    };
+
+    // Act
+    let result = service.render_output("default", &snippet, DEFAULT_OUTPUT)?;
+
+    // Assert
+    assert_eq!(result, "awk '{print $0}' \u{E000}");
+    Ok(())
+}
@@ -0,0 +1,150 @@
+// application/services/tests/validation_tests.rs
+use anyhow::Result;
+use rsnip::application::services::ValidationService;
+use rsnip::config::{AliasConfig, Settings, SnippetTypeConfig};
+use rsnip::infrastructure::minijinja::{MiniJinjaEngine, SafeShellExecutor};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::NamedTempFile;
+
+fn create_test_settings(path: &Path) -> Settings {
+    let mut snippet_types = HashMap::new();
+    snippet_types.insert(
+        "test".to_string(),
+        SnippetTypeConfig::Concrete {
+            source_file: path.to_path_buf(),
+            description: None,
+            alias: None,
+            format: "default".to_string(),
+            vars: HashMap::new(),
+            output: None,
+            aliases: AliasConfig::default(),
+        },
+    );
+
+    Settings {
+        snippet_types,
+        config_paths: vec![],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn given_broken_templates_when_checking_then_reports_file_lines() -> Result<()> {
+    // Arrange
+    let mut temp_file = NamedTempFile::new()?;
+    write!(
+        temp_file,
+        "--- good\n{{{{ cwd }}}} {{{{ name|default('x') }}}}\n---\n\
+         --- bad\n: comment\n\nfirst\n\n{{{{ missing|nofilter }}}}\n{{{{ 'rm x'|shell }}}}\n---\n\
+         --- syntax\n{{% if %}}\n---\n"
+    )?;
+    let settings = create_test_settings(temp_file.path());
+    let engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()));
+    let service = ValidationService::new(&settings);
+
+    // Act
+    let diagnostics = service.check(&engine, Some("test"))?;
+
+    // Assert
+    let file = temp_file.path().display();
+    let lines: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(lines.len(), 4, "Unexpected diagnostics: {:?}", lines);
+    assert_eq!(lines[0], format!("{}:9: bad: undefined variable 'missing'", file));
+    assert_eq!(lines[1], format!("{}:9: bad: unknown filter 'nofilter'", file));
+    assert!(lines[2].starts_with(&format!("{}:10: bad: shell command 'rm x'", file)));
+    assert!(lines[3].starts_with(&format!("{}:13: syntax: syntax error", file)));
+    Ok(())
+}
+
+#[test]
+fn given_unknown_output_header_when_checking_then_reports_it() -> Result<()> {
+    // Arrange
+    let mut temp_file = NamedTempFile::new()?;
+    write!(
+        temp_file,
+        "--- diff\n: output: prints the diff\ngit diff\n: output: shout\n---\n\
+         --- payload\n: output: dedent\n  x\n---\n"
+    )?;
+    let settings = create_test_settings(temp_file.path());
+    let engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()));
+    let service = ValidationService::new(&settings);
+
+    // Act
+    let diagnostics = service.check(&engine, Some("test"))?;
+
+    // Assert
+    let lines: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(lines.len(), 1, "Unexpected diagnostics: {:?}", lines);
+    assert!(lines[0].starts_with(&format!(
+        "{}:2: diff: Unknown output transform 'prints the diff'",
+        temp_file.path().display()
+    )));
+    Ok(())
+}
+
+#[test]
+fn given_type_vars_when_checking_all_types_then_uses_vars_of_each_type() -> Result<()> {
+    // Arrange: each type uses the var only its own configuration defines
+    let mut aws_file = NamedTempFile::new()?;
+    write!(aws_file, "--- region\n{{{{ region }}}}\n---\n")?;
+    let mut sql_file = NamedTempFile::new()?;
+    write!(sql_file, "--- db\n{{{{ database }}}} {{{{ region }}}}\n---\n")?;
+    let mut settings = create_test_settings(aws_file.path());
+    let with_vars = |path: &Path, name: &str| SnippetTypeConfig::Concrete {
+        source_file: path.to_path_buf(),
+        description: None,
+        alias: None,
+        format: "default".to_string(),
+        vars: HashMap::from([(name.to_string(), serde_json::json!("x"))]),
+        output: None,
+        aliases: AliasConfig::default(),
+    };
+    settings.snippet_types.insert("test".to_string(), with_vars(aws_file.path(), "region"));
+    settings.snippet_types.insert("sql".to_string(), with_vars(sql_file.path(), "database"));
+    let engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()))
+        .with_variable_resolver(Arc::new(settings.clone()));
+    let service = ValidationService::new(&settings);
+
+    // Act
+    let diagnostics = service.check(&engine, None)?;
+
+    // Assert
+    let lines: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(
+        lines,
+        vec![format!("{}:2: db: undefined variable 'region'", sql_file.path().display())]
+    );
+    Ok(())
+}
+
+#[test]
+fn given_invalid_snippet_file_when_checking_then_reports_parse_error() -> Result<()> {
+    // Arrange
+    let mut temp_file = NamedTempFile::new()?;
+    write!(temp_file, "--- unclosed\ncontent\n")?;
+    let settings = create_test_settings(temp_file.path());
+    let engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()));
+    let service = ValidationService::new(&settings);
+
+    // Act
+    let diagnostics = service.check(&engine, None)?;
+
+    // Assert
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].line, 1);
+    assert!(diagnostics[0].snippet.is_empty());
+    Ok(())
+}
+
+#[test]
+fn given_unknown_type_when_checking_then_fails() {
+    let settings = Settings::default();
+    let engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()));
+
+    let result = ValidationService::new(&settings).check(&engine, Some("nope"));
+
+    assert!(result.is_err());
+}
@@ -1,6 +1,8 @@
 use anyhow::Result;
-use rsnip::config::{Settings, SnippetTypeConfig};
+use rsnip::config::{AliasConfig, Settings, SnippetTypeConfig};
+use rsnip::domain::output::OutputTransform;
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::io::Write;
 use tempfile::NamedTempFile;
 use rsnip::application::snippet_service::SnippetService;
@@ -15,13 +17,16 @@ fn create_test_settings_single(source_file: std::path::PathBuf) -> Settings {
             description: None,
             alias: None,
             format: "default".to_string(),
+            vars: HashMap::new(),
+            output: None,
+            aliases: AliasConfig::default(),
         },
     );
 
     Settings {
         snippet_types,
         config_paths: vec![],
-        active_config_path: None,
+        ..Default::default()
     }
 }
 
@@ -68,3 +73,105 @@ fn given_template_snippet_when_copying_then_returns_rendered_content() -> Result
     Ok(())
 }
 
+
+#[test]
+fn given_file_template_snippet_when_planning_scaffold_then_renders_manifest() -> Result<()> {
+    // Arrange
+    let mut temp_file = NamedTempFile::new()?;
+    writeln!(
+        temp_file,
+        "--- module\n== path: src/{{{{ 'parser' }}}}.rs ==\npub fn parse() {{}}\n---\n--- plain\njust text\n---"
+    )?;
+    let settings = create_test_settings_single(temp_file.path().to_path_buf());
+    let template_engine = Box::new(MiniJinjaEngine::new(Box::new(SafeShellExecutor::new())));
+    let service = SnippetService::new(template_engine, &settings);
+
+    // Act
+    let plan = service.plan_scaffold("test", "module", std::path::Path::new("out"))?;
+
+    // Assert
+    assert_eq!(plan.entries.len(), 1);
+    assert_eq!(plan.entries[0].path, std::path::PathBuf::from("src/parser.rs"));
+    assert_eq!(plan.entries[0].content.as_deref(), Some("pub fn parse() {}\n"));
+    let err = service
+        .plan_scaffold("test", "plain", std::path::Path::new("out"))
+        .unwrap_err();
+    assert!(err.to_string().contains("not a file template"));
+    Ok(())
+}
+
+#[test]
+fn given_snippet_with_cursor_marker_when_expanding_then_returns_text_and_cursor() -> Result<()> {
+    // Arrange
+    let mut temp_file = NamedTempFile::new()?;
+    writeln!(temp_file, "--- gc\ngit commit -m \"$0\" {{{{ 'now' }}}}\n---")?;
+    let settings = create_test_settings_single(temp_file.path().to_path_buf());
+    let template_engine = Box::new(MiniJinjaEngine::new(Box::new(SafeShellExecutor::new())));
+    let service = SnippetService::new(template_engine, &settings);
+
+    // Act
+    let expansion = service.expand_snippet("test", "gc")?;
+
+    // Assert
+    let expansion = expansion.expect("snippet should be found");
+    assert_eq!(expansion.text, "git commit -m \"\" now");
+    assert_eq!(expansion.cursor, 15);
+    assert!(service.expand_snippet("test", "g")?.is_none());
+    Ok(())
+}
+
+#[test]
+fn given_template_snippet_when_rendering_then_keeps_cursor_marker() -> Result<()> {
+    // Arrange
+    let mut temp_file = NamedTempFile::new()?;
+    writeln!(temp_file, "--- greet\nHello {{{{ 'World' }}}}$0\n---")?;
+    let settings = create_test_settings_single(temp_file.path().to_path_buf());
+    let template_engine = Box::new(MiniJinjaEngine::new(Box::new(SafeShellExecutor::new())));
+    let service = SnippetService::new(template_engine, &settings);
+
+    // Act
+    let rendered = service.render_snippet("test", "greet")?;
+
+    // Assert
+    let (snippet, text) = rendered.expect("snippet should be found");
+    assert_eq!(snippet.name, "greet");
+    assert_eq!(text, "Hello World$0");
+    assert!(service.render_snippet("test", "gre")?.is_none());
+    Ok(())
+}
+
+#[test]
+fn given_snippet_of_combined_type_when_expanding_then_renders_as_its_own_type() -> Result<()> {
+    // Arrange
+    let mut temp_file = NamedTempFile::new()?;
+    writeln!(temp_file, "--- hello\necho {{{{ region }}}}\n---")?;
+    let mut settings = create_test_settings_single(temp_file.path().to_path_buf());
+    if let Some(SnippetTypeConfig::Concrete { vars, .. }) = settings.snippet_types.get_mut("test") {
+        vars.insert("region".to_string(), serde_json::json!("us-east-1"));
+    }
+    settings.snippet_types.insert(
+        "all".to_string(),
+        SnippetTypeConfig::Combined {
+            sources: vec!["test".to_string()],
+            description: None,
+            alias: None,
+            vars: HashMap::from([("region".to_string(), serde_json::json!("eu"))]),
+            output: Some(vec![OutputTransform::ShellQuote]),
+            aliases: AliasConfig::default(),
+        },
+    );
+    let template_engine = Box::new(
+        MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()))
+            .with_variable_resolver(Arc::new(settings.clone())),
+    );
+    let service = SnippetService::new(template_engine, &settings);
+
+    // Act
+    let own = service.expand_snippet("test", "hello")?.expect("snippet should be found");
+    let combined = service.expand_snippet("all", "hello")?.expect("snippet should be found");
+
+    // Assert
+    assert_eq!(own.text, "echo us-east-1");
+    assert_eq!(combined, own);
+    Ok(())
+}
@@ -1,2 +1,3 @@
 mod parsers;
-mod test_minijinja;
\ No newline at end of file
+mod test_minijinja;
+mod test_tmux;
\ No newline at end of file
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use rsnip::domain::content::SnippetContent;
+use rsnip::domain::output::OutputTransform;
 use rsnip::domain::parser::SnippetFormat;
 use rsnip::domain::snippet::Snippet;
 use rsnip::infrastructure::parsers::SnippetParserFactory;
@@ -44,7 +45,9 @@ this is other
         Snippet {
             name: "apple".to_string(),
             content: SnippetContent::Static("this is green\nand nothing else".to_string()),
-            comments: vec![]
+            comments: vec![],
+            language: None,
+            output: None,
         }
     );
     assert_eq!(
@@ -52,7 +55,9 @@ this is other
         Snippet {
             name: "aple".to_string(),
             content: SnippetContent::Static("this is green2".to_string()),
-            comments: vec![]
+            comments: vec![],
+            language: None,
+            output: None,
         }
     );
     assert_eq!(
@@ -60,7 +65,9 @@ this is other
         Snippet {
             name: "banana".to_string(),
             content: SnippetContent::Static("this is yellow".to_string()),
-            comments: vec![]
+            comments: vec![],
+            language: None,
+            output: None,
         }
     );
     assert_eq!(
@@ -68,7 +75,9 @@ this is other
         Snippet {
             name: "else".to_string(),
             content: SnippetContent::Static("this is other".to_string()),
-            comments: vec![]
+            comments: vec![],
+            language: None,
+            output: None,
         }
     );
     Ok(())
@@ -140,7 +149,9 @@ random trailing text
         Snippet {
             name: "apple".to_string(),
             content: SnippetContent::Static("line".to_string()),
-            comments: vec![]
+            comments: vec![],
+            language: None,
+            output: None,
         }
     );
     Ok(())
@@ -179,6 +190,66 @@ and nothing else
     Ok(())
 }
 
+#[test]
+fn given_language_metadata_when_parse_then_sets_language_instead_of_comment() -> Result<()> {
+    // Arrange
+    let content = "--- backup\n: Backup a directory\n: language: bash\ntar czf backup.tgz .\n---";
+    let mut temp_file = NamedTempFile::new()?;
+    writeln!(temp_file, "{}", content)?;
+    let parser = SnippetParserFactory::create(SnippetFormat::Default);
+
+    // Act
+    let snippets = parser.parse(temp_file.path())?;
+
+    // Assert
+    assert_eq!(snippets[0].comments, vec!["Backup a directory"]);
+    assert_eq!(snippets[0].language.as_deref(), Some("bash"));
+    Ok(())
+}
+
+#[test]
+fn given_metadata_like_comment_outside_header_when_parse_then_keeps_it_as_comment() -> Result<()> {
+    // Arrange
+    let content = "--- note\n: language: see the wiki for details\necho one\n: output: goes to stdout\necho two\n---";
+    let mut temp_file = NamedTempFile::new()?;
+    writeln!(temp_file, "{}", content)?;
+    let parser = SnippetParserFactory::create(SnippetFormat::Default);
+
+    // Act
+    let snippets = parser.parse(temp_file.path())?;
+
+    // Assert
+    assert_eq!(
+        snippets[0].comments,
+        vec!["language: see the wiki for details", "output: goes to stdout"]
+    );
+    assert_eq!(snippets[0].language, None);
+    assert_eq!(snippets[0].output, None);
+    Ok(())
+}
+
+#[test]
+fn given_output_metadata_when_parse_then_sets_output_pipeline() -> Result<()> {
+    // Arrange
+    let content = "--- payload\n: output: dedent, json_escape\n  {\"a\": 1}\n---\n--- diff\n: output: prints the diff\ngit diff\n---";
+    let mut temp_file = NamedTempFile::new()?;
+    writeln!(temp_file, "{}", content)?;
+    let parser = SnippetParserFactory::create(SnippetFormat::Default);
+
+    // Act
+    let snippets = parser.parse(temp_file.path())?;
+
+    // Assert
+    assert_eq!(
+        snippets[0].output,
+        Some(vec![OutputTransform::Dedent, OutputTransform::JsonEscape])
+    );
+    assert!(snippets[0].comments.is_empty());
+    assert_eq!(snippets[1].output, None);
+    assert_eq!(snippets[1].comments, vec!["output: prints the diff"]);
+    Ok(())
+}
+
 #[test]
 fn given_nonexistent_file_when_parse_then_returns_error() -> Result<()> {
     // Arrange
@@ -36,6 +36,7 @@ body = "def ${1:name}(${2:args}):\n    ${3:pass}"
     assert_eq!(snippets[0].comments.len(), 2);
     assert_eq!(snippets[0].comments[0], "Simple print statement");
     assert_eq!(snippets[0].comments[1], "Scope: python");
+    assert_eq!(snippets[0].language.as_deref(), Some("python"));
 
     // Check second snippet
     assert_eq!(snippets[1].name, "func");
@@ -15,7 +15,8 @@ fn given_valid_vscode_file_when_parse_then_returns_snippets() -> Result<()> {
                     "    println!(\"Hello, world!\");",
                     "}"
                 ],
-                "description": "Insert a simple Rust Hello World program"
+                "description": "Insert a simple Rust Hello World program",
+                "scope": "rust, markdown"
             },
             "Rust Function": {
                 "prefix": "rust-fn",
@@ -60,6 +61,8 @@ fn given_valid_vscode_file_when_parse_then_returns_snippets() -> Result<()> {
         "fn ${1:function_name}(${2:params}) -> ${3:ReturnType} {\n    ${4:// function body}\n}"
     );
     assert_eq!(fn_snippet.comments, vec!["Create a Rust function template"]);
+    assert_eq!(hello_snippet.language.as_deref(), Some("rust"));
+    assert_eq!(fn_snippet.language, None);
 
     Ok(())
 }
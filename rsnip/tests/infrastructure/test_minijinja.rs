@@ -1,10 +1,15 @@
-use chrono::Utc;
+use chrono::Local;
 use rsnip::domain::content::SnippetContent;
-use rsnip::domain::template::interface::TemplateEngine;
+use rsnip::domain::template::errors::TemplateError;
+use rsnip::domain::template::interface::{
+    ShellCommandExecutor, SnippetResolver, TemplateEngine, UserPrompter, VariableResolver,
+};
+use rsnip::infrastructure::dates::{Clock, TemplateTimezone};
+use rsnip::infrastructure::env_vars::EnvPolicy;
 use rsnip::infrastructure::minijinja::{MiniJinjaEngine, SafeShellExecutor};
 
 // Helper function to create a template engine instance
-fn create_engine() -> impl TemplateEngine {
+fn create_engine() -> MiniJinjaEngine {
     MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()))
 }
 
@@ -15,7 +20,7 @@ fn given_static_content_when_rendering_then_returns_unchanged() {
     let content = SnippetContent::Static("Hello World".to_string());
 
     // Act
-    let result = engine.render(&content).unwrap();
+    let result = engine.render("default", &content).unwrap();
 
     // Assert
     assert_eq!(result, "Hello World");
@@ -27,11 +32,10 @@ fn given_template_with_date_when_rendering_then_formats_date() {
     let engine = create_engine();
     let content = SnippetContent::Template {
         source: "{{ current_date | strftime('%Y-%m-%d') }}".to_string(),
-        compiled: None,
     };
 
     // Act
-    let result = engine.render(&content).unwrap();
+    let result = engine.render("default", &content).unwrap();
 
     // Assert
     let date_regex = regex::Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap();
@@ -44,33 +48,85 @@ fn given_template_with_date_arithmetic_when_rendering_then_calculates_correctly(
     let engine = create_engine();
     let content = SnippetContent::Template {
         source: "{{ current_date | subtract_days(7) | strftime('%Y-%m-%d') }}".to_string(),
-        compiled: None,
     };
 
     // Act
-    let result = engine.render(&content).unwrap();
+    let result = engine.render("default", &content).unwrap();
 
     // Assert
-    let today = Utc::now();
+    let today = Local::now();
     let week_ago = today - chrono::Duration::days(7);
     assert_eq!(result, week_ago.format("%Y-%m-%d").to_string());
 }
 
 #[test]
-fn given_template_with_env_var_when_rendering_then_substitutes_value() {
+fn given_template_with_env_function_when_rendering_then_substitutes_value() {
     // Arrange
     std::env::set_var("TEST_VAR", "test_value");
     let engine = create_engine();
     let content = SnippetContent::Template {
-        source: "{{ env_TEST_VAR }}".to_string(),
-        compiled: None,
+        source: "{{ env('TEST_VAR') }} {{ env('RSNIP_TEST_UNSET', 'fallback') }}".to_string(),
     };
 
     // Act
-    let result = engine.render(&content).unwrap();
+    let result = engine.render("default", &content).unwrap();
 
     // Assert
-    assert_eq!(result, "test_value");
+    assert_eq!(result, "test_value fallback");
+}
+
+#[test]
+fn given_legacy_env_vars_when_rendering_then_exposes_env_prefixed_values() {
+    // Arrange
+    std::env::set_var("TEST_LEGACY_VAR", "legacy_value");
+    std::env::set_var("TEST_LEGACY_TOKEN", "hidden");
+    let engine = create_engine().with_legacy_env_vars(true);
+    let content = SnippetContent::new(
+        "{{ env_TEST_LEGACY_VAR }} {{ env_TEST_LEGACY_TOKEN is defined }}".to_string(),
+    );
+
+    // Act
+    let result = engine.render("default", &content).unwrap();
+
+    // Assert
+    assert_eq!(result, "legacy_value False");
+}
+
+#[test]
+fn given_default_engine_when_rendering_then_env_prefixed_values_are_undefined() {
+    // Arrange
+    std::env::set_var("TEST_PLAIN_VAR", "value");
+    let engine = create_engine();
+    let content = SnippetContent::new("{{ env_TEST_PLAIN_VAR is defined }}".to_string());
+
+    // Act
+    let result = engine.render("default", &content).unwrap();
+
+    // Assert
+    assert_eq!(result, "False");
+}
+
+#[test]
+fn given_denied_env_var_when_rendering_then_fails_unless_allowlisted() {
+    // Arrange
+    std::env::set_var("TEST_GITHUB_TOKEN", "secret");
+    let content = SnippetContent::new("{{ env('TEST_GITHUB_TOKEN') }}".to_string());
+    let default_engine = create_engine();
+    let allowlisted_engine = create_engine().with_env_policy(EnvPolicy::new(
+        vec!["TEST_GITHUB_TOKEN".to_string()],
+        vec!["*TOKEN*".to_string()],
+    ));
+
+    // Act
+    let denied = default_engine.render("default", &content);
+    let allowed = allowlisted_engine.render("default", &content);
+
+    // Assert
+    assert!(denied
+        .unwrap_err()
+        .to_string()
+        .contains("TEST_GITHUB_TOKEN is not exposed to templates"));
+    assert_eq!(allowed.unwrap(), "secret");
 }
 
 #[test]
@@ -79,11 +135,10 @@ fn given_template_with_shell_command_when_rendering_then_executes() {
     let engine = create_engine();
     let content = SnippetContent::Template {
         source: "{{ 'echo Hello' | shell }}".to_string(),
-        compiled: None,
     };
 
     // Act
-    let result = engine.render(&content).unwrap();
+    let result = engine.render("default", &content).unwrap();
 
     // Assert
     assert_eq!(result, "Hello");
@@ -95,11 +150,10 @@ fn given_template_with_invalid_shell_command_when_rendering_then_returns_error()
     let engine = create_engine();
     let content = SnippetContent::Template {
         source: "{{ 'nonexistent_command' | shell }}".to_string(),
-        compiled: None,
     };
 
     // Act & Assert
-    assert!(engine.render(&content).is_err());
+    assert!(engine.render("default", &content).is_err());
 }
 
 #[test]
@@ -119,10 +173,9 @@ fn given_template_with_dangerous_shell_commands_then_returns_error() {
     for cmd in dangerous_commands {
         let content = SnippetContent::Template {
             source: format!("{{{{ '{}' | shell }}}}", cmd),
-            compiled: None,
         };
         assert!(
-            engine.render(&content).is_err(),
+            engine.render("default", &content).is_err(),
             "Should reject dangerous command: {}",
             cmd
         );
@@ -135,14 +188,716 @@ fn given_template_with_add_days_when_rendering_then_calculates_correctly() {
     let engine = create_engine();
     let content = SnippetContent::Template {
         source: "{{ current_date | add_days(7) | strftime('%Y-%m-%d') }}".to_string(),
-        compiled: None,
     };
 
     // Act
-    let result = engine.render(&content).unwrap();
+    let result = engine.render("default", &content).unwrap();
 
     // Assert
-    let today = Utc::now();
+    let today = Local::now();
     let next_week = today + chrono::Duration::days(7);
     assert_eq!(result, next_week.format("%Y-%m-%d").to_string());
-}
\ No newline at end of file
+}
+#[test]
+fn given_template_with_cwd_when_rendering_then_substitutes_working_directory() {
+    // Arrange
+    let engine = create_engine();
+    let content = SnippetContent::Template {
+        source: "{{ cwd }}|{{ cwd_name }}".to_string(),
+    };
+    let cwd = std::env::current_dir().unwrap();
+
+    // Act
+    let result = engine.render("default", &content).unwrap();
+
+    // Assert
+    let expected = format!(
+        "{}|{}",
+        cwd.display(),
+        cwd.file_name().unwrap().to_string_lossy()
+    );
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn given_template_with_system_info_when_rendering_then_substitutes_os_and_arch() {
+    // Arrange
+    let engine = create_engine();
+    let content = SnippetContent::Template {
+        source: "{{ os }}/{{ arch }} {{ hostname is defined }} {{ clipboard is defined }}".to_string(),
+    };
+
+    // Act
+    let result = engine.render("default", &content).unwrap();
+
+    // Assert
+    assert_eq!(
+        result,
+        format!("{}/{} True True", std::env::consts::OS, std::env::consts::ARCH)
+    );
+}
+
+// Helper to render a template source with the default engine
+fn render(source: &str) -> Result<String, TemplateError> {
+    let engine = create_engine();
+    engine.render("default", &SnippetContent::Template {
+        source: source.to_string(),
+    })
+}
+
+#[test]
+fn given_case_filters_when_rendering_then_converts_case() {
+    assert_eq!(render("{{ 'Hello World-foo' | snake_case }}").unwrap(), "hello_world_foo");
+    assert_eq!(render("{{ 'hello_world foo' | camel_case }}").unwrap(), "helloWorldFoo");
+    assert_eq!(render("{{ 'HelloWorld foo' | kebab_case }}").unwrap(), "hello-world-foo");
+    assert_eq!(render("{{ 'hello-world_foo' | pascal_case }}").unwrap(), "HelloWorldFoo");
+}
+
+#[test]
+fn given_slugify_filter_when_rendering_then_returns_slug() {
+    assert_eq!(
+        render("{{ '  Hello, World! Ünïcode 42 ' | slugify }}").unwrap(),
+        "hello-world-ünïcode-42"
+    );
+    assert_eq!(render("{{ 'Ünïcode straße' | slugify }}").unwrap(), "ünïcode-straße");
+}
+
+#[test]
+fn given_base64_filters_when_rendering_then_round_trips() {
+    assert_eq!(render("{{ 'hello world' | base64_encode }}").unwrap(), "aGVsbG8gd29ybGQ=");
+    assert_eq!(render("{{ 'aGVsbG8gd29ybGQ=' | base64_decode }}").unwrap(), "hello world");
+    assert!(render("{{ 'not base64!' | base64_decode }}").is_err());
+}
+
+#[test]
+fn given_url_encode_filter_when_rendering_then_percent_encodes() {
+    assert_eq!(
+        render("{{ 'a b&c=d/é' | url_encode }}").unwrap(),
+        "a%20b%26c%3Dd%2F%C3%A9"
+    );
+}
+
+#[test]
+fn given_hash_filters_when_rendering_then_returns_hex_digest() {
+    assert_eq!(
+        render("{{ 'abc' | sha256 }}").unwrap(),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    assert_eq!(
+        render("{{ 'abc' | md5 }}").unwrap(),
+        "900150983cd24fb0d6963f7d28e17f72"
+    );
+}
+
+#[test]
+fn given_uuid4_function_when_rendering_then_returns_random_uuid() {
+    let uuid_regex = regex::Regex::new(
+        r"^[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$",
+    )
+    .unwrap();
+
+    let first = render("{{ uuid4() }}").unwrap();
+    let second = render("{{ uuid4() }}").unwrap();
+
+    assert!(uuid_regex.is_match(&first), "Not a v4 uuid: {}", first);
+    assert_ne!(first, second);
+}
+
+#[test]
+fn given_random_int_function_when_rendering_then_stays_in_range() {
+    for _ in 0..20 {
+        let value: i64 = render("{{ random_int(3, 5) }}").unwrap().parse().unwrap();
+        assert!((3..=5).contains(&value));
+    }
+    assert!(render("{{ random_int(5, 3) }}").is_err());
+}
+
+#[test]
+fn given_random_string_function_when_rendering_then_returns_alphanumeric_of_length() {
+    let result = render("{{ random_string(16) }}").unwrap();
+
+    assert_eq!(result.len(), 16);
+    assert!(result.chars().all(|c| c.is_ascii_alphanumeric()));
+}
+
+#[test]
+fn given_regex_replace_filter_when_rendering_then_replaces_all_matches() {
+    assert_eq!(
+        render(r"{{ 'v1.2.3' | regex_replace('(\\d+)', '<$1>') }}").unwrap(),
+        "v<1>.<2>.<3>"
+    );
+    assert!(render("{{ 'x' | regex_replace('(', '') }}").is_err());
+}
+
+#[test]
+fn given_indent_and_dedent_filters_when_rendering_then_adjusts_indentation() {
+    assert_eq!(
+        render("{{ 'a\\nb' | indent(2) }}").unwrap(),
+        "a\n  b"
+    );
+    assert_eq!(
+        render("{{ '    a\\n      b\\n\\n    c' | dedent }}").unwrap(),
+        "a\n  b\n\nc"
+    );
+}
+
+#[test]
+fn given_shell_quote_filter_when_rendering_then_quotes_for_posix_shell() {
+    assert_eq!(render("{{ 'simple/path-1.txt' | shell_quote }}").unwrap(), "simple/path-1.txt");
+    assert_eq!(render("{{ 'it\\'s here' | shell_quote }}").unwrap(), r#"'it'\''s here'"#);
+    assert_eq!(render("{{ '' | shell_quote }}").unwrap(), "''");
+}
+
+// Helper to render with an engine fixed to the given timezone
+fn render_in_tz(timezone: &str, source: &str) -> String {
+    let timezone: TemplateTimezone = timezone.parse().unwrap();
+    let engine =
+        MiniJinjaEngine::new(Box::new(SafeShellExecutor::new())).with_clock(Clock::new(timezone));
+    engine
+        .render("default", &SnippetContent::Template {
+            source: source.to_string(),
+        })
+        .unwrap()
+}
+
+#[test]
+fn given_configured_timezone_when_rendering_current_date_then_uses_its_offset() {
+    // Arrange & Act
+    let result = render_in_tz("Asia/Kolkata", "{{ current_date }}");
+
+    // Assert
+    assert!(result.ends_with("+05:30"), "Unexpected offset: {}", result);
+}
+
+#[test]
+fn given_date_with_offset_when_formatting_then_keeps_local_day() {
+    // Evening in New York is already the next day in UTC
+    let result = render_in_tz(
+        "UTC",
+        "{{ '2025-03-10T22:00:00-04:00' | strftime('%Y-%m-%d %H:%M') }}",
+    );
+
+    assert_eq!(result, "2025-03-10 22:00");
+}
+
+#[test]
+fn given_to_tz_filter_when_rendering_then_converts_timezone() {
+    let result = render_in_tz(
+        "UTC",
+        "{{ '2025-07-01T12:00:00+00:00' | to_tz('America/New_York') }}",
+    );
+
+    assert_eq!(result, "2025-07-01T08:00:00-04:00");
+}
+
+#[test]
+fn given_arithmetic_filters_when_rendering_then_shifts_dates() {
+    let source = "{{ '2024-01-31T10:00:00+00:00' | add_hours(3) }}|\
+                  {{ '2024-01-31T10:00:00+00:00' | add_weeks(2) | strftime('%Y-%m-%d') }}|\
+                  {{ '2024-01-31T10:00:00+00:00' | add_months(1) | strftime('%Y-%m-%d') }}|\
+                  {{ '2024-01-31T10:00:00+00:00' | add_months(-2) | strftime('%Y-%m-%d') }}";
+
+    let result = render_in_tz("UTC", source);
+
+    assert_eq!(
+        result,
+        "2024-01-31T13:00:00+00:00|2024-02-14|2024-02-29|2023-11-30"
+    );
+}
+
+#[test]
+fn given_next_weekday_filter_when_rendering_then_returns_following_day() {
+    // 2025-01-17 is a Friday
+    let result = render_in_tz(
+        "UTC",
+        "{{ '2025-01-15' | next_weekday('friday') | strftime('%a %Y-%m-%d') }} \
+         {{ '2025-01-17' | next_weekday('friday') | strftime('%Y-%m-%d') }}",
+    );
+
+    assert_eq!(result, "Fri 2025-01-17 2025-01-24");
+}
+
+#[test]
+fn given_iso_week_filter_when_rendering_then_returns_week_number() {
+    let result = render_in_tz(
+        "UTC",
+        "{{ '2024-12-30' | iso_week }} {{ '2024-12-30' | strftime('%G-W%V') }}",
+    );
+
+    assert_eq!(result, "1 2025-W01");
+}
+
+#[test]
+fn given_relative_expression_when_rendering_then_resolves_date() {
+    let result = render_in_tz(
+        "UTC",
+        "{{ parse_date('next monday') | strftime('%A') }} {{ 'tomorrow' | strftime('%H:%M') }}",
+    );
+
+    assert_eq!(result, "Monday 00:00");
+}
+
+#[test]
+fn given_fixed_now_when_rendering_then_current_date_is_frozen() {
+    // Arrange
+    let now = chrono::DateTime::parse_from_rfc3339("2025-01-15T09:00:00+01:00").unwrap();
+    let engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()))
+        .with_clock(Clock::default().with_fixed_now(now));
+    let content = SnippetContent::Template {
+        source: "{{ current_date }} {{ 'tomorrow' | strftime('%Y-%m-%d') }}".to_string(),
+    };
+
+    // Act
+    let result = engine.render("default", &content).unwrap();
+
+    // Assert
+    assert_eq!(result, "2025-01-15T09:00:00+01:00 2025-01-16");
+}
+
+#[test]
+fn given_same_seed_when_rendering_then_random_output_is_identical() {
+    // Arrange
+    let content = SnippetContent::Template {
+        source: "{{ uuid4() }} {{ random_int(1, 1000000) }} {{ random_string(20) }}".to_string(),
+    };
+    let seeded = |seed| MiniJinjaEngine::new(Box::new(SafeShellExecutor::new())).with_seed(seed);
+
+    // Act
+    let first = seeded(42).render("default", &content).unwrap();
+    let second = seeded(42).render("default", &content).unwrap();
+    let other = seeded(7).render("default", &content).unwrap();
+
+    // Assert
+    assert_eq!(first, second);
+    assert_ne!(first, other);
+}
+
+// In-memory resolver for snippet composition tests
+struct MapResolver(std::collections::HashMap<&'static str, &'static str>);
+
+impl SnippetResolver for MapResolver {
+    fn resolve(&self, reference: &str) -> Result<Option<String>, TemplateError> {
+        Ok(self.0.get(reference).map(|source| source.to_string()))
+    }
+}
+
+fn create_engine_with_snippets(snippets: &[(&'static str, &'static str)]) -> MiniJinjaEngine {
+    let resolver = MapResolver(snippets.iter().cloned().collect());
+    MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()))
+        .with_snippet_resolver(std::sync::Arc::new(resolver))
+}
+
+#[test]
+fn given_include_of_other_snippet_when_rendering_then_inlines_it() {
+    // Arrange
+    let engine = create_engine_with_snippets(&[("shell:header", "# License: {{ license }}")]);
+    let content = SnippetContent::new(
+        "{% set license = 'MIT' %}{% include 'shell:header' %}\necho hi".to_string(),
+    );
+
+    // Act
+    let result = engine.render("default", &content).unwrap();
+
+    // Assert
+    assert_eq!(result, "# License: MIT\necho hi");
+}
+
+#[test]
+fn given_snippet_function_when_rendering_then_renders_with_caller_context() {
+    // Arrange
+    let engine = create_engine_with_snippets(&[("greeting", "Hello {{ who }}")]);
+    let content = SnippetContent::new("{% set who = 'World' %}{{ snippet('greeting') }}!".to_string());
+
+    // Act
+    let result = engine.render("default", &content).unwrap();
+
+    // Assert
+    assert_eq!(result, "Hello World!");
+}
+
+#[test]
+fn given_macro_snippet_when_importing_then_macros_are_callable() {
+    // Arrange
+    let engine = create_engine_with_snippets(&[(
+        "shell:macros",
+        "{% macro shebang(shell='bash') %}#!/usr/bin/env {{ shell }}{% endmacro %}",
+    )]);
+    let content = SnippetContent::new(
+        "{% from 'shell:macros' import shebang %}{{ shebang('zsh') }}".to_string(),
+    );
+
+    // Act
+    let result = engine.render("default", &content).unwrap();
+
+    // Assert
+    assert_eq!(result, "#!/usr/bin/env zsh");
+}
+
+#[test]
+fn given_reference_cycle_when_rendering_then_error_names_chain() {
+    // Arrange
+    let engine = create_engine_with_snippets(&[
+        ("shell:a", "A {% include 'shell:b' %}"),
+        ("shell:b", "B {{ snippet('shell:a') }}"),
+    ]);
+    let content = SnippetContent::new("{% include 'shell:a' %}".to_string());
+
+    // Act
+    let err = engine.render("default", &content).unwrap_err();
+
+    // Assert
+    assert!(matches!(err, TemplateError::Reference(_)));
+    assert!(
+        err.to_string().contains("shell:a -> shell:b -> shell:a"),
+        "Unexpected error: {}",
+        err
+    );
+}
+
+#[test]
+fn given_missing_reference_when_rendering_then_error_names_chain() {
+    // Arrange
+    let engine = create_engine_with_snippets(&[("shell:a", "{% include 'shell:missing' %}")]);
+    let content = SnippetContent::new("{% include 'shell:a' %}".to_string());
+
+    // Act
+    let err = engine.render("default", &content).unwrap_err();
+
+    // Assert
+    assert!(
+        err.to_string().contains("shell:a -> shell:missing"),
+        "Unexpected error: {}",
+        err
+    );
+}
+
+#[test]
+fn given_references_that_are_not_rendered_when_rendering_then_ignores_them() {
+    // Arrange
+    let engine = create_engine_with_snippets(&[("shell:a", "A\n")]);
+    let sources = [
+        "x{% include 'nope' ignore missing %}y",
+        "{% if false %}{% include 'gone' %}{% endif %}ok",
+        "{# {% include 'gone' %} #}ok",
+        "{% raw %}{% include 'gone' %}{% endraw %}",
+    ];
+
+    // Act
+    let results: Vec<String> = sources
+        .iter()
+        .map(|source| engine.render("default", &SnippetContent::new(source.to_string())).unwrap())
+        .collect();
+
+    // Assert
+    assert_eq!(results, vec!["xy", "ok", "ok", "{% include 'gone' %}"]);
+    assert_eq!(
+        engine.render("default", &SnippetContent::new("{% include 'shell:a' %}!".to_string())).unwrap(),
+        "A!"
+    );
+}
+
+#[test]
+fn given_template_library_when_rendering_then_macros_are_global() {
+    // Arrange
+    let mut library = tempfile::NamedTempFile::new().unwrap();
+    std::io::Write::write_all(
+        &mut library,
+        b"{% set jira_base = 'https://jira.example.com/browse/' %}\n\
+          {% macro jira_link(id, text=none) %}[{{ text or id }}]({{ jira_base }}{{ id }}){% endmacro %}\n\
+          {% macro signed() %}-- {{ author }}{% endmacro %}\n",
+    )
+    .unwrap();
+    let mut engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()));
+    engine.load_template_library(library.path()).unwrap();
+    let content = SnippetContent::new(
+        "{{ jira_link('ABC-1') }} {{ jira_link('ABC-2', text='fix') }} {{ jira_base }}\n\
+         {% set author = 'me' %}{{ signed() }}"
+            .to_string(),
+    );
+
+    // Act
+    let result = engine.render("default", &content).unwrap();
+
+    // Assert
+    assert_eq!(
+        result,
+        "[ABC-1](https://jira.example.com/browse/ABC-1) [fix](https://jira.example.com/browse/ABC-2) \
+         https://jira.example.com/browse/\n-- me"
+    );
+}
+
+#[test]
+fn given_modified_template_library_when_rendering_then_reloads_it() {
+    // Arrange
+    let library = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(library.path(), "{% macro greet() %}hi{% endmacro %}{% set old = 1 %}").unwrap();
+    let mut engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()));
+    engine.load_template_library(library.path()).unwrap();
+    let content = SnippetContent::new("{{ greet() }} {{ old is defined }}".to_string());
+    let first = engine.render("default", &content).unwrap();
+
+    // Act
+    std::fs::write(library.path(), "{% macro greet() %}hello{% endmacro %}").unwrap();
+    let modified = std::time::SystemTime::now() + std::time::Duration::from_secs(10);
+    library.as_file().set_modified(modified).unwrap();
+    let second = engine.render("default", &content).unwrap();
+
+    // Assert
+    assert_eq!(first, "hi True");
+    assert_eq!(second, "hello False");
+}
+
+#[test]
+fn given_template_library_with_syntax_error_when_loading_then_reports_file_and_line() {
+    // Arrange
+    let mut library = tempfile::NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut library, b"{% macro ok() %}ok{% endmacro %}\n{% macro broken( %}\n")
+        .unwrap();
+    let mut engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()));
+
+    // Act
+    let err = engine.load_template_library(library.path()).unwrap_err();
+
+    // Assert
+    let expected_location = format!("{}:2", library.path().display());
+    assert!(
+        err.to_string().contains(&expected_location),
+        "Unexpected error: {}",
+        err
+    );
+}
+
+#[test]
+fn given_user_vars_when_rendering_then_later_vars_override_earlier() {
+    // Arrange
+    let engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()))
+        .with_vars([
+            ("jira_project", serde_json::json!("OPS")),
+            ("default_region", serde_json::json!("eu-central-1")),
+            ("regions", serde_json::json!(["eu", "us"])),
+        ])
+        .with_vars([("default_region", serde_json::json!("us-east-1"))]);
+    let content = SnippetContent::new(
+        "{{ jira_project }}-1 {{ default_region }} {{ regions|join(',') }}".to_string(),
+    );
+
+    // Act
+    let result = engine.render("default", &content).unwrap();
+
+    // Assert
+    assert_eq!(result, "OPS-1 us-east-1 eu,us");
+}
+
+/// Vars of the `shell` and `sql` types, like in the configuration
+struct TypeVars;
+
+impl VariableResolver for TypeVars {
+    fn vars(&self, snippet_type: &str) -> std::collections::HashMap<String, serde_json::Value> {
+        let region = match snippet_type {
+            "shell" => "eu-central-1",
+            "sql" => "us-east-1",
+            _ => return Default::default(),
+        };
+        [("region".to_string(), serde_json::json!(region))].into()
+    }
+}
+
+#[test]
+fn given_type_vars_when_rendering_then_uses_vars_of_the_snippet_type() {
+    // Arrange
+    let engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()))
+        .with_variable_resolver(std::sync::Arc::new(TypeVars));
+    let cli_engine = MiniJinjaEngine::new(Box::new(SafeShellExecutor::new()))
+        .with_variable_resolver(std::sync::Arc::new(TypeVars))
+        .with_vars([("region", serde_json::json!("ap-south-1"))]);
+    let content = SnippetContent::new("{{ region | default('none') }}".to_string());
+
+    // Act
+    let shell = engine.render("shell", &content).unwrap();
+    let sql = engine.render("sql", &content).unwrap();
+    let other = engine.render("default", &content).unwrap();
+    let overridden = cli_engine.render("shell", &content).unwrap();
+
+    // Assert
+    assert_eq!(shell, "eu-central-1");
+    assert_eq!(sql, "us-east-1");
+    assert_eq!(other, "none");
+    assert_eq!(overridden, "ap-south-1");
+    assert!(engine.check("sql", &SnippetContent::new("{{ region }}".to_string())).is_empty());
+}
+
+/// Answers questions from a script and records what was asked
+struct ScriptedPrompter {
+    interactive: bool,
+    asked: std::sync::Mutex<Vec<String>>,
+}
+
+impl ScriptedPrompter {
+    fn new(interactive: bool) -> std::sync::Arc<Self> {
+        std::sync::Arc::new(Self {
+            interactive,
+            asked: std::sync::Mutex::new(Vec::new()),
+        })
+    }
+
+    fn asked(&self) -> Vec<String> {
+        self.asked.lock().unwrap().clone()
+    }
+}
+
+impl UserPrompter for ScriptedPrompter {
+    fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+
+    fn input(&self, label: &str, default: Option<&str>) -> Result<String, TemplateError> {
+        self.asked.lock().unwrap().push(label.to_string());
+        Ok(format!("{}!", default.unwrap_or("typed")))
+    }
+
+    fn confirm(&self, label: &str, default: bool) -> Result<bool, TemplateError> {
+        self.asked.lock().unwrap().push(label.to_string());
+        Ok(!default)
+    }
+
+    fn select(&self, label: &str, options: &[String], _default: usize) -> Result<usize, TemplateError> {
+        self.asked.lock().unwrap().push(label.to_string());
+        Ok(options.len() - 1)
+    }
+}
+
+#[test]
+fn given_repeated_questions_when_rendering_then_asks_each_once_per_render() {
+    // Arrange
+    let prompter = ScriptedPrompter::new(true);
+    let engine = create_engine().with_prompter(prompter.clone());
+    let content = SnippetContent::new(
+        "{{ prompt('Ticket id', default='ABC') }}/{{ prompt('Ticket id') }} \
+         {{ confirm('Force?') }} {{ choose('Env', ['dev', 'stage', 'prod']) }}"
+            .to_string(),
+    );
+
+    // Act
+    let first = engine.render("default", &content).unwrap();
+    engine.render("default", &content).unwrap();
+
+    // Assert
+    assert_eq!(first, "ABC!/ABC! True prod");
+    assert_eq!(
+        prompter.asked(),
+        vec!["Ticket id", "Force?", "Env", "Ticket id", "Force?", "Env"]
+    );
+}
+
+#[test]
+fn given_preset_answers_when_not_interactive_then_uses_answers_without_asking() {
+    // Arrange
+    let prompter = ScriptedPrompter::new(false);
+    let engine = create_engine()
+        .with_prompter(prompter.clone())
+        .with_answers([("Ticket id", "OPS-7"), ("Force?", "yes"), ("Env", "stage")]);
+    let content = SnippetContent::new(
+        "{{ prompt('Ticket id') }} {{ confirm('Force?') }} {{ choose('Env', ['dev', 'stage']) }}"
+            .to_string(),
+    );
+
+    // Act
+    let result = engine.render("default", &content).unwrap();
+
+    // Assert
+    assert_eq!(result, "OPS-7 True stage");
+    assert!(prompter.asked().is_empty());
+}
+
+#[test]
+fn given_no_terminal_and_no_answer_when_rendering_then_fails_with_hint() {
+    // Arrange
+    let engine = create_engine().with_prompter(ScriptedPrompter::new(false));
+    let content = SnippetContent::new("{{ prompt('Ticket id') }}".to_string());
+
+    // Act
+    let err = engine.render("default", &content).unwrap_err();
+
+    // Assert
+    assert!(
+        err.to_string().contains("--answer 'Ticket id=VALUE'"),
+        "Unexpected error: {}",
+        err
+    );
+}
+
+#[test]
+fn given_answer_not_in_options_when_choosing_then_fails() {
+    // Arrange
+    let engine = create_engine()
+        .with_prompter(ScriptedPrompter::new(false))
+        .with_answers([("Env", "qa")]);
+    let content = SnippetContent::new("{{ choose('Env', ['dev', 'prod']) }}".to_string());
+
+    // Act
+    let err = engine.render("default", &content).unwrap_err();
+
+    // Assert
+    assert!(err.to_string().contains("is not one of: dev, prod"));
+}
+
+/// Counts executed commands instead of running them
+#[derive(Clone, Default)]
+struct CountingExecutor {
+    calls: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+}
+
+impl ShellCommandExecutor for CountingExecutor {
+    fn execute(&self, command: &str) -> Result<String, TemplateError> {
+        let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+        Ok(format!("{}#{}", command, call))
+    }
+
+    fn box_clone(&self) -> Box<dyn ShellCommandExecutor> {
+        Box::new(self.clone())
+    }
+}
+
+#[test]
+fn given_preview_when_rendering_then_does_not_prompt_and_reuses_shell_output() {
+    // Arrange
+    let executor = CountingExecutor::default();
+    let prompter = ScriptedPrompter::new(true);
+    let engine = MiniJinjaEngine::new(Box::new(executor.clone())).with_prompter(prompter.clone());
+    let content = SnippetContent::new(
+        "{{ 'date'|shell }} {{ prompt('Ticket id') }} {{ prompt('Env', default='dev') }} \
+         {{ confirm('Force?') }} {{ choose('Region', ['eu', 'us']) }}"
+            .to_string(),
+    );
+
+    // Act
+    let first = engine.render_preview("default", &content).unwrap();
+    let second = engine.render_preview("default", &content).unwrap();
+    let rendered = engine
+        .render("default", &SnippetContent::new("{{ 'date'|shell }}".to_string()))
+        .unwrap();
+
+    // Assert
+    assert_eq!(first, "date#1 <Ticket id> dev False eu");
+    assert_eq!(second, first);
+    assert_eq!(rendered, "date#2");
+    assert!(prompter.asked().is_empty());
+}
+
+#[test]
+fn given_preview_of_composed_snippet_when_rendering_then_referenced_snippets_do_not_prompt() {
+    // Arrange
+    let prompter = ScriptedPrompter::new(true);
+    let engine = create_engine_with_snippets(&[("shell:ticket", "{{ prompt('Ticket id') }}")])
+        .with_prompter(prompter.clone());
+    let content = SnippetContent::new(
+        "{% include 'shell:ticket' %} {{ snippet('shell:ticket') }}".to_string(),
+    );
+
+    // Act
+    let result = engine.render_preview("default", &content).unwrap();
+
+    // Assert
+    assert_eq!(result, "<Ticket id> <Ticket id>");
+    assert!(prompter.asked().is_empty());
+}
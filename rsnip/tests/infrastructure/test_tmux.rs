@@ -0,0 +1,104 @@
+use rsnip::infrastructure::tmux::Tmux;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+/// Private tmux server running `cat` in a single pane, killed on drop
+struct TmuxServer {
+    socket: String,
+}
+
+impl TmuxServer {
+    /// Start a server on a socket unique to this test
+    fn start(test: &str) -> Self {
+        let socket = format!("rsnip-test-{}-{}", std::process::id(), test);
+        let status = Command::new("tmux")
+            .args(["-L", &socket, "-f", "/dev/null", "new-session", "-d", "-x", "80", "-y", "10", "cat"])
+            .status()
+            .expect("Failed to run tmux");
+        assert!(status.success(), "Failed to start tmux server");
+        Self { socket }
+    }
+
+    fn tmux(&self, args: &[&str]) -> String {
+        let output = Command::new("tmux")
+            .args(["-L", &self.socket])
+            .args(args)
+            .output()
+            .expect("Failed to run tmux");
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    fn pane_id(&self) -> String {
+        self.tmux(&["display-message", "-p", "#{pane_id}"]).trim().to_string()
+    }
+
+    /// Visible pane content, waiting until it contains `expected` or giving up
+    fn wait_for_pane(&self, expected: &str) -> String {
+        let mut content = String::new();
+        for _ in 0..50 {
+            content = self.tmux(&["capture-pane", "-p"]);
+            if content.contains(expected) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        content
+    }
+}
+
+impl Drop for TmuxServer {
+    fn drop(&mut self) {
+        let _ = Command::new("tmux")
+            .args(["-L", &self.socket, "kill-server"])
+            .status();
+    }
+}
+
+#[test]
+#[ignore = "Requires tmux"]
+fn given_text_when_loading_buffer_then_buffer_holds_text() {
+    // Arrange
+    let server = TmuxServer::start("buffer");
+    let tmux = Tmux::new().with_socket(&server.socket);
+
+    // Act
+    tmux.load_buffer("git status\n--short", Some("snippet")).unwrap();
+    tmux.load_buffer("on top", None).unwrap();
+
+    // Assert
+    assert_eq!(server.tmux(&["show-buffer", "-b", "snippet"]), "git status\n--short");
+    assert_eq!(server.tmux(&["show-buffer"]), "on top");
+}
+
+#[test]
+#[ignore = "Requires tmux"]
+fn given_pane_when_sending_then_text_is_typed_and_buffer_removed() {
+    // Arrange
+    let server = TmuxServer::start("pane");
+    let tmux = Tmux::new().with_socket(&server.socket);
+    let pane = server.pane_id();
+
+    // Act
+    tmux.send_to_pane("echo from-rsnip", Some(&pane)).unwrap();
+
+    // Assert
+    assert!(server.wait_for_pane("echo from-rsnip").contains("echo from-rsnip"));
+    assert!(!server.tmux(&["list-buffers", "-F", "#{buffer_name}"]).contains("rsnip-"));
+}
+
+#[test]
+#[ignore = "Requires tmux"]
+fn given_unknown_pane_when_sending_then_returns_tmux_error_and_removes_buffer() {
+    // Arrange
+    let server = TmuxServer::start("unknown");
+    let tmux = Tmux::new().with_socket(&server.socket);
+
+    // Act
+    let result = tmux.send_to_pane("text", Some("%999"));
+
+    // Assert
+    let err = result.unwrap_err().to_string();
+    assert!(err.starts_with("tmux paste-buffer failed:"), "{}", err);
+    assert_eq!(server.tmux(&["list-buffers"]), "");
+}
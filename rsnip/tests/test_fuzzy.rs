@@ -1,4 +1,8 @@
-use rsnip::infrastructure::fuzzy::{create_skim_items, run_fuzzy_finder};
+use rsnip::infrastructure::fuzzy::{create_preview_skim_items, create_skim_items, run_fuzzy_finder, PreviewOptions};
+use rsnip::infrastructure::highlight::Highlighter;
+use rsnip::infrastructure::minijinja::{MiniJinjaEngine, SafeShellExecutor};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use skim::{ItemPreview, PreviewContext};
 use std::path::PathBuf;
 use rsnip::domain::content::SnippetContent;
@@ -13,16 +17,22 @@ fn create_test_data() -> (Vec<Snippet>, SnippetType) {
             name: "apple".to_string(),
             content: SnippetContent::Static("This is an apple".to_string()),
             comments: Vec::new(),
+            language: None,
+            output: None,
         },
         Snippet {
             name: "apricot".to_string(),
             content: SnippetContent::Static("This is an apricot".to_string()),
             comments: Vec::new(),
+            language: None,
+            output: None,
         },
         Snippet {
             name: "banana".to_string(),
             content: SnippetContent::Static("This is a banana".to_string()),
             comments: Vec::new(),
+            language: None,
+            output: None,
         },
     ];
 
@@ -100,11 +110,15 @@ fn given_snippets_when_creating_skim_items_then_returns_formatted_items() {
             name: "test1".to_string(),
             content: SnippetContent::Static("line1\nline2\nline3".to_string()),
             comments: vec!["A test comment".to_string()],
+            language: None,
+            output: None,
         },
         Snippet {
             name: "test2".to_string(),
             content: SnippetContent::Static("".to_string()),
             comments: Vec::new(),
+            language: None,
+            output: None,
         },
     ];
 
@@ -174,6 +188,93 @@ fn given_no_matches_when_fuzzy_finder_then_shows_interface() -> Result<()> {
 fn test_fuzzy_finder_output_is_clean() -> Result<()> {
     let (items, _) = create_test_data();
     let result = run_fuzzy_finder(&items, "test")?;
-    assert!(result.as_ref().map_or(true, |s| !s.contains("\x1B[")));
+    assert!(result.as_ref().is_none_or(|s| !s.contains("\x1B[")));
     Ok(())
-}
\ No newline at end of file
+}
+
+fn preview_text(item: &Arc<dyn skim::SkimItem>) -> String {
+    let preview_context = PreviewContext {
+        query: "",
+        cmd_query: "",
+        width: 0,
+        height: 0,
+        current_index: 0,
+        current_selection: "",
+        selected_indices: &[],
+        selections: &[],
+    };
+    match item.preview(preview_context) {
+        ItemPreview::AnsiText(preview) => preview,
+        _ => panic!("Expected AnsiText preview"),
+    }
+}
+
+#[test]
+fn given_rendered_preview_toggled_when_previewing_then_shows_rendered_output_or_error() {
+    // Arrange
+    let snippets = vec![
+        Snippet {
+            name: "greeting".to_string(),
+            content: SnippetContent::new("Hello {{ 'world'|upper }}".to_string()),
+            comments: Vec::new(),
+            language: None,
+            output: None,
+        },
+        Snippet {
+            name: "broken".to_string(),
+            content: SnippetContent::new("{{ 1|nofilter }}".to_string()),
+            comments: Vec::new(),
+            language: None,
+            output: None,
+        },
+    ];
+    let engine = Arc::new(MiniJinjaEngine::new(Box::new(SafeShellExecutor::new())));
+    let show_rendered = Arc::new(AtomicBool::new(false));
+    let preview = PreviewOptions {
+        engine: Some(engine),
+        highlighter: None,
+
+        ..Default::default()
+    };
+    let items = create_preview_skim_items(&snippets, preview, show_rendered.clone());
+
+    // Act
+    let source_preview = preview_text(&items[0]);
+    show_rendered.store(true, Ordering::Relaxed);
+    let rendered_preview = preview_text(&items[0]);
+    let error_preview = preview_text(&items[1]);
+
+    // Assert
+    assert!(source_preview.contains("Hello {{ 'world'|upper }}"));
+    assert!(source_preview.contains("alt-r: show rendered"));
+    assert!(rendered_preview.contains("Hello WORLD"));
+    assert!(rendered_preview.contains("Rendered"));
+    assert!(error_preview.contains("unknown filter"));
+    assert!(error_preview.contains("\x1B["), "Error should be colored");
+}
+
+#[test]
+fn given_highlighter_when_previewing_code_snippet_then_content_is_colored() {
+    // Arrange
+    let snippets = vec![Snippet {
+        name: "main".to_string(),
+        content: SnippetContent::Static("fn main() {}".to_string()),
+        comments: Vec::new(),
+        language: Some("rust".to_string()),
+        output: None,
+    }];
+    let preview = PreviewOptions {
+        engine: None,
+        highlighter: Some(Arc::new(Highlighter::new(None).unwrap())),
+
+        ..Default::default()
+    };
+
+    // Act
+    let items = create_preview_skim_items(&snippets, preview, Arc::new(AtomicBool::new(false)));
+    let text = preview_text(&items[0]);
+
+    // Assert
+    assert!(text.contains("\x1b[38;2;"), "Expected 24-bit colors in: {:?}", text);
+    assert!(!text.contains("alt-r"), "Static snippets have nothing to render");
+}
@@ -0,0 +1,124 @@
+// test_lsp
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Command, Stdio};
+use tempfile::TempDir;
+
+/// Frame `message` as the language server expects it on stdin
+fn frame(message: Value) -> String {
+    let body = message.to_string();
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+/// Split the server's stdout into messages
+fn read_messages(output: &[u8]) -> Result<Vec<Value>> {
+    let mut reader = BufReader::new(output);
+    let mut messages = Vec::new();
+    loop {
+        let mut length = None;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 {
+                return Ok(messages);
+            }
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length: ") {
+                length = Some(value.parse::<usize>()?);
+            }
+        }
+        let mut body = vec![0; length.ok_or_else(|| anyhow!("Missing Content-Length"))?];
+        reader.read_exact(&mut body)?;
+        messages.push(serde_json::from_slice(&body)?);
+    }
+}
+
+/// Response to the request `id` among `messages`
+fn response(messages: &[Value], id: i64) -> &Value {
+    messages
+        .iter()
+        .find(|message| message["id"] == id)
+        .unwrap_or_else(|| panic!("No response to request {}", id))
+}
+
+#[test]
+fn given_scripted_client_when_completing_then_offers_and_resolves_snippets() -> Result<()> {
+    // Arrange: a hermetic config with shell snippets, one of them a template
+    let home = TempDir::new()?;
+    let config_dir = home.path().join(".config/rsnip");
+    fs::create_dir_all(&config_dir)?;
+    let snippets = home.path().join("shell.sh");
+    fs::write(
+        &snippets,
+        "--- gc\n: Commit all\ngit commit -m \"$0\" --all\n---\n\
+         --- sum\necho {{ 1 + 1 }} $PWD\n---\n\
+         --- venv\n: language: python\nimport venv\n---\n",
+    )?;
+    fs::write(
+        config_dir.join("config.toml"),
+        format!("[snippet_types.shell]\nsource_file = \"{}\"\n", snippets.display()),
+    )?;
+
+    let uri = "file:///tmp/deploy.sh";
+    let script = [
+        json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"capabilities": {}}}),
+        json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+        json!({"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {"textDocument": {
+            "uri": uri, "languageId": "shellscript", "version": 1, "text": ""
+        }}}),
+        json!({"jsonrpc": "2.0", "id": 2, "method": "textDocument/completion", "params": {
+            "textDocument": {"uri": uri}, "position": {"line": 0, "character": 0}
+        }}),
+        json!({"jsonrpc": "2.0", "id": 3, "method": "completionItem/resolve", "params": {
+            "label": "sum", "data": {"ctype": "shell", "name": "sum"}
+        }}),
+        json!({"jsonrpc": "2.0", "id": 4, "method": "shutdown"}),
+        json!({"jsonrpc": "2.0", "method": "exit"}),
+    ];
+
+    // Act
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rsnip"))
+        .arg("lsp")
+        .env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path().join(".config"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let input: String = script.into_iter().map(frame).collect();
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("No stdin"))?
+        .write_all(input.as_bytes())?;
+    let output = child.wait_with_output()?;
+
+    // Assert
+    assert!(output.status.success());
+    let messages = read_messages(&output.stdout)?;
+
+    let initialize = &response(&messages, 1)["result"];
+    assert_eq!(initialize["serverInfo"]["name"], "rsnip");
+    assert_eq!(initialize["capabilities"]["completionProvider"]["resolveProvider"], true);
+
+    let items = response(&messages, 2)["result"]
+        .as_array()
+        .ok_or_else(|| anyhow!("No completion items"))?;
+    let labels: Vec<&str> = items.iter().filter_map(|item| item["label"].as_str()).collect();
+    assert_eq!(labels, vec!["gc", "sum"]);
+    assert_eq!(items[0]["detail"], "Commit all");
+    assert_eq!(items[0]["insertText"], r#"git commit -m "$0" --all"#);
+    assert_eq!(items[0]["insertTextFormat"], 2);
+    assert!(items[1].get("insertText").is_none());
+
+    let resolved = &response(&messages, 3)["result"];
+    assert_eq!(resolved["insertText"], r"echo 2 \$PWD");
+    assert_eq!(resolved["insertTextFormat"], 2);
+
+    assert!(response(&messages, 4)["result"].is_null());
+    Ok(())
+}